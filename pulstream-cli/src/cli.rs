@@ -0,0 +1,798 @@
+//! Command-line argument parsing for the `pulstream` binary.
+
+use pulstream_plugin::plugins::pumpfun_tracking::{DuplicateSlotPolicy, TimestampSource};
+use serde::{Serialize, Serializer};
+use std::{error::Error, str::FromStr};
+
+/// How decoded trade events are emitted (`--format`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Log each event at info level.
+    #[default]
+    Log,
+    /// Length-delimited protobuf messages written to `--output`.
+    Protobuf,
+    /// Length-prefixed bincode records written to `--output`.
+    Bincode,
+}
+
+impl OutputFormat {
+    /// File extension for outputs in this format, or `None` if it does not
+    /// write to a file.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::Log => None,
+            Self::Protobuf => Some("pb"),
+            Self::Bincode => Some("bin"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "log" => Ok(Self::Log),
+            "protobuf" => Ok(Self::Protobuf),
+            "bincode" => Ok(Self::Bincode),
+            other => Err(format!(
+                "unknown --format value: {other} (expected log, protobuf or bincode)"
+            )),
+        }
+    }
+}
+
+/// Options collected from the command line. Serialized for `--print-config`,
+/// with secrets redacted.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CliArgs {
+    /// Token mint address to track (`--mint`/`-m`).
+    pub mint: Option<String>,
+    /// Skip the startup sanity check of the mint address (`--no-mint-check`).
+    pub no_mint_check: bool,
+    /// Read the end of a `<start>:<end>` slot range as exclusive (`--exclusive-end`).
+    pub exclusive_end: bool,
+    /// Seconds between heartbeat logs while no trades match (`--heartbeat-interval`).
+    pub heartbeat_interval: Option<u64>,
+    /// Log and skip transactions that fail to decode (`--skip-errors`).
+    pub skip_errors: bool,
+    /// Check decoded amounts against token balance deltas (`--verify-balances`).
+    pub verify_balances: bool,
+    /// Allowed difference in raw token units for `--verify-balances`
+    /// (`--balance-tolerance`).
+    pub balance_tolerance: u64,
+    /// Check decoded trade directions against reserve movements
+    /// (`--check-direction`).
+    pub check_direction: bool,
+    /// Flag trades that are part of a sandwich (`--detect-sandwiches`).
+    pub detect_sandwiches: bool,
+    /// Output format for trade events (`--format`).
+    pub format: OutputFormat,
+    /// Output target for non-log formats (`--output`/`-o`): `-` for stdout,
+    /// `unix:<path>` for a Unix socket, `null` to drop events, or a file path.
+    pub output: Option<String>,
+    /// Directory to write an auto-named output file into (`--output-dir`).
+    pub output_dir: Option<String>,
+    /// Roll file outputs after this many bytes (`--rotate-size`, e.g. `100MB`).
+    pub rotate_size: Option<u64>,
+    /// Roll file outputs after this many events (`--rotate-events`).
+    pub rotate_events: Option<u64>,
+    /// Also emit migration (graduation) events (`--track-migrations`).
+    pub track_migrations: bool,
+    /// Also emit new holder (token account creation) events (`--track-holders`).
+    pub track_holders: bool,
+    /// Also emit SPL token transfers of the mint (`--track-transfers`).
+    pub track_transfers: bool,
+    /// Lowest instruction stack height to emit events for (`--min-stack-height`).
+    pub min_stack_height: Option<u32>,
+    /// Highest instruction stack height to emit events for (`--max-stack-height`).
+    pub max_stack_height: Option<u32>,
+    /// Lowest bonding curve progress of emitted trades, 0.0 to 1.0
+    /// (`--min-curve-progress`).
+    pub min_curve_progress: Option<f64>,
+    /// Highest bonding curve progress of emitted trades, 0.0 to 1.0
+    /// (`--max-curve-progress`).
+    pub max_curve_progress: Option<f64>,
+    /// Emit trades without reserve data despite a curve progress range
+    /// (`--pass-missing-curve-progress`).
+    pub pass_missing_curve_progress: bool,
+    /// Milliseconds to sleep before every sink write, for testing slow
+    /// outputs (`--simulate-latency`).
+    pub simulate_latency: Option<u64>,
+    /// Seconds between flush+fsync of file outputs (`--sync-interval`).
+    pub sync_interval: Option<u64>,
+    /// Epoch length used to resolve an epoch argument (`--slots-per-epoch`).
+    pub slots_per_epoch: Option<u64>,
+    /// Read `<start>:<end>` ranges line by line from a file or `-` for stdin
+    /// (`--ranges-from`), replacing the positional range.
+    pub ranges_from: Option<String>,
+    /// Program whose undecodable instructions are dumped (`--dump-undecoded`).
+    pub dump_undecoded: Option<String>,
+    /// Estimate unique payers with bounded memory (`--approx-unique`).
+    pub approx_unique: bool,
+    /// Exit with an error if the mint never appears (`--strict-mint`).
+    pub strict_mint: bool,
+    /// Attach each trade's account list (`--include-accounts`).
+    pub include_accounts: bool,
+    /// Key under which wallet addresses are replaced by their HMAC-SHA256
+    /// (`--hash-payers`).
+    #[serde(serialize_with = "redact")]
+    pub hash_payers: Option<String>,
+    /// Process slots from high to low in chunks of
+    /// [`crate::range::REVERSE_CHUNK_SLOTS`], each a runner of its own
+    /// (`--reverse`).
+    pub reverse: bool,
+    /// Record and report per-hook plugin latency (`--profile`).
+    pub profile: bool,
+    /// Seconds between mid-run flushes of all sinks (`--flush-interval`).
+    pub flush_interval: Option<u64>,
+    /// Only process transactions paid for by these wallets (`--fee-payer`,
+    /// repeatable).
+    pub fee_payers: Vec<String>,
+    /// Skip transactions referencing these programs (`--exclude-program`,
+    /// repeatable).
+    pub excluded_programs: Vec<String>,
+    /// Skip failed transactions (`--success-only`).
+    pub success_only: bool,
+    /// Half-life in seconds of the per-mint average price (`--price-halflife`).
+    pub price_halflife: Option<u64>,
+    /// Attach the block time to trades (`--block-time`).
+    pub block_time: bool,
+    /// Time used for the `timestamp` field (`--timestamp-source`).
+    pub timestamp_source: TimestampSource,
+    /// Slots at the start of the run whose events are not emitted
+    /// (`--warmup-slots`).
+    pub warmup_slots: u64,
+    /// Handling of slots delivered more than once (`--on-duplicate`).
+    pub on_duplicate: DuplicateSlotPolicy,
+    /// Only emit trades made by the mint's creator (`--only-creator`).
+    pub only_creator: bool,
+    /// Emit one summary per block instead of trades (`--block-summary`).
+    pub block_summary: bool,
+    /// Emit each payer's net position at exit instead of trades
+    /// (`--net-positions`).
+    pub net_positions: bool,
+    /// Most payers tracked by `--net-positions` (`--net-positions-max-payers`).
+    pub net_positions_max_payers: Option<usize>,
+    /// Log per-mint trade inter-arrival histograms at exit (`--interarrival`).
+    pub interarrival: bool,
+    /// Collector receiving trades as JSON lines over TCP (`--tcp`,
+    /// `host:port`).
+    pub tcp: Option<String>,
+    /// TOML file routing each event type to its own sink (`--routes`).
+    pub routes: Option<String>,
+    /// Check the decoder against embedded transactions first (`--self-test`).
+    pub self_test: bool,
+    /// Write each mint's events to its own file (`--split-by-mint`).
+    pub split_by_mint: bool,
+    /// Skip inner instructions (`--top-level-only`).
+    pub top_level_only: bool,
+    /// Largest instruction data passed to the decoder
+    /// (`--max-instruction-data`, e.g. `10KB`).
+    pub max_instruction_data: Option<u64>,
+    /// Transaction to export as a replay bundle during the run
+    /// (`--export-signature`).
+    pub export_signature: Option<String>,
+    /// Write every matched raw transaction as JSON lines (`--archive-raw`).
+    pub archive_raw: Option<String>,
+    /// Path of the exported bundle (`--export-out`), `<signature>.bundle` by
+    /// default.
+    pub export_out: Option<String>,
+    /// Replay a bundle through the plugin instead of streaming slots
+    /// (`--replay-transaction`).
+    pub replay_transaction: Option<String>,
+    /// Slots per market breadth window (`--breadth-window`).
+    pub breadth_window: Option<u64>,
+    /// Number of mints to rank by volume at exit (`--top`).
+    pub top: Option<usize>,
+    /// Seconds per VWAP window (`--vwap-window`, e.g. `60s` or `5m`).
+    pub vwap_window: Option<u64>,
+    /// Annotate trades with the token symbol (`--enrich-metadata`).
+    pub enrich_metadata: bool,
+    /// Metadata lookups allowed at once (`--enrich-concurrency`).
+    pub enrich_concurrency: Option<usize>,
+    /// RPC endpoint for metadata lookups (`--rpc-url`).
+    #[serde(serialize_with = "redact_query")]
+    pub rpc_url: Option<String>,
+    /// Path of the persistent set of emitted events (`--dedup-store`).
+    pub dedup_store: Option<String>,
+    /// Unix socket accepting pause/resume/status commands (`--control-socket`).
+    pub control_socket: Option<String>,
+    /// Path of the machine-readable status file (`--status-file`).
+    pub status_file: Option<String>,
+    /// Seconds between status file updates (`--status-interval`).
+    pub status_interval: Option<u64>,
+    /// ClickHouse DSN for the runner (`--clickhouse-url`).
+    pub clickhouse_url: Option<String>,
+    /// Run without ClickHouse rather than fail when `--clickhouse-url` is
+    /// invalid (`--require-db off`).
+    pub db_optional: bool,
+    /// Print the resolved configuration as JSON before running, to stderr,
+    /// or to the file given as `--print-config=<path>` (`--print-config`).
+    pub print_config: Option<Option<String>>,
+    /// Remaining positional arguments, in order.
+    pub positionals: Vec<String>,
+}
+
+impl CliArgs {
+    /// Parses `args` (including the program name at index 0).
+    pub fn parse(args: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut cli = CliArgs::default();
+        let mut i = 1;
+        while i < args.len() {
+            let a = &args[i];
+            if let Some(value) = take_value(args, &mut i, &["--mint", "-m"])? {
+                cli.mint = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--heartbeat-interval"])? {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --heartbeat-interval value: {value}"))?;
+                cli.heartbeat_interval = Some(secs);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--format"])? {
+                cli.format = value.parse()?;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--timestamp-source"])? {
+                cli.timestamp_source = value.parse()?;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--on-duplicate"])? {
+                cli.on_duplicate = value.parse()?;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--output", "-o"])? {
+                cli.output = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--balance-tolerance"])? {
+                cli.balance_tolerance = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --balance-tolerance value: {value}"))?;
+                continue;
+            }
+            if a == "--verify-balances" {
+                cli.verify_balances = true;
+                i += 1;
+                continue;
+            }
+            if a == "--check-direction" {
+                cli.check_direction = true;
+                i += 1;
+                continue;
+            }
+            if a == "--print-config" {
+                cli.print_config = Some(None);
+                i += 1;
+                continue;
+            }
+            if let Some(path) = a.strip_prefix("--print-config=") {
+                cli.print_config = Some(Some(path.to_string()));
+                i += 1;
+                continue;
+            }
+            if a == "--detect-sandwiches" {
+                cli.detect_sandwiches = true;
+                i += 1;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--rotate-size"])? {
+                cli.rotate_size = Some(parse_size(&value)?);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--max-instruction-data"])? {
+                cli.max_instruction_data = Some(parse_size(&value)?);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--rotate-events"])? {
+                let events = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --rotate-events value: {value}"))?;
+                cli.rotate_events = Some(events);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--min-stack-height"])? {
+                cli.min_stack_height = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid --min-stack-height value: {value}"))?,
+                );
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--max-stack-height"])? {
+                cli.max_stack_height = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid --max-stack-height value: {value}"))?,
+                );
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--min-curve-progress"])? {
+                cli.min_curve_progress =
+                    Some(parse_curve_progress("--min-curve-progress", &value)?);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--max-curve-progress"])? {
+                cli.max_curve_progress =
+                    Some(parse_curve_progress("--max-curve-progress", &value)?);
+                continue;
+            }
+            if a == "--pass-missing-curve-progress" {
+                cli.pass_missing_curve_progress = true;
+                i += 1;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--sync-interval"])? {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --sync-interval value: {value}"))?;
+                cli.sync_interval = Some(secs);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--simulate-latency"])? {
+                let millis = value
+                    .trim()
+                    .strip_suffix("ms")
+                    .unwrap_or(value.trim())
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --simulate-latency value: {value}"))?;
+                cli.simulate_latency = Some(millis);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--flush-interval"])? {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --flush-interval value: {value}"))?;
+                cli.flush_interval = Some(secs);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--price-halflife"])? {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --price-halflife value: {value}"))?;
+                cli.price_halflife = Some(secs);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--warmup-slots"])? {
+                cli.warmup_slots = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --warmup-slots value: {value}"))?;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--net-positions-max-payers"])? {
+                let payers = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --net-positions-max-payers value: {value}"))?;
+                cli.net_positions_max_payers = Some(payers);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--top"])? {
+                let n = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --top value: {value}"))?;
+                cli.top = Some(n);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--breadth-window"])? {
+                let slots = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --breadth-window value: {value}"))?;
+                cli.breadth_window = Some(slots);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--vwap-window"])? {
+                cli.vwap_window = Some(parse_duration_secs(&value)?);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--slots-per-epoch"])? {
+                let slots = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --slots-per-epoch value: {value}"))?;
+                cli.slots_per_epoch = Some(slots);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--output-dir"])? {
+                cli.output_dir = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--ranges-from"])? {
+                cli.ranges_from = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--dump-undecoded"])? {
+                cli.dump_undecoded = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--hash-payers"])? {
+                cli.hash_payers = Some(value);
+                continue;
+            }
+            if a == "--reverse" {
+                cli.reverse = true;
+                i += 1;
+                continue;
+            }
+            if a == "--profile" {
+                cli.profile = true;
+                i += 1;
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--exclude-program"])? {
+                cli.excluded_programs.push(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--fee-payer"])? {
+                cli.fee_payers.push(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--export-signature"])? {
+                cli.export_signature = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--tcp"])? {
+                cli.tcp = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--routes"])? {
+                cli.routes = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--control-socket"])? {
+                cli.control_socket = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--archive-raw"])? {
+                cli.archive_raw = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--export-out"])? {
+                cli.export_out = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--replay-transaction"])? {
+                cli.replay_transaction = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--enrich-concurrency"])? {
+                let limit = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --enrich-concurrency value: {value}"))?;
+                cli.enrich_concurrency = Some(limit);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--dedup-store"])? {
+                cli.dedup_store = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--status-file"])? {
+                cli.status_file = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--status-interval"])? {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid --status-interval value: {value}"))?;
+                cli.status_interval = Some(secs);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--clickhouse-url"])? {
+                cli.clickhouse_url = Some(value);
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--require-db"])? {
+                cli.db_optional = match value.as_str() {
+                    "on" => false,
+                    "off" => true,
+                    other => {
+                        return Err(format!(
+                            "invalid --require-db value: {other} (expected on or off)"
+                        )
+                        .into())
+                    }
+                };
+                continue;
+            }
+            if let Some(value) = take_value(args, &mut i, &["--rpc-url"])? {
+                cli.rpc_url = Some(value);
+                continue;
+            }
+            if a == "--enrich-metadata" {
+                cli.enrich_metadata = true;
+                i += 1;
+                continue;
+            }
+            if a == "--no-mint-check" {
+                cli.no_mint_check = true;
+                i += 1;
+                continue;
+            }
+            if a == "--exclusive-end" {
+                cli.exclusive_end = true;
+                i += 1;
+                continue;
+            }
+            if a == "--track-migrations" {
+                cli.track_migrations = true;
+                i += 1;
+                continue;
+            }
+            if a == "--track-transfers" {
+                cli.track_transfers = true;
+                i += 1;
+                continue;
+            }
+            if a == "--track-holders" {
+                cli.track_holders = true;
+                i += 1;
+                continue;
+            }
+            if a == "--block-time" {
+                cli.block_time = true;
+                i += 1;
+                continue;
+            }
+            if a == "--block-summary" {
+                cli.block_summary = true;
+                i += 1;
+                continue;
+            }
+            if a == "--net-positions" {
+                cli.net_positions = true;
+                i += 1;
+                continue;
+            }
+            if a == "--interarrival" {
+                cli.interarrival = true;
+                i += 1;
+                continue;
+            }
+            if a == "--self-test" {
+                cli.self_test = true;
+                i += 1;
+                continue;
+            }
+            if a == "--split-by-mint" {
+                cli.split_by_mint = true;
+                i += 1;
+                continue;
+            }
+            if a == "--top-level-only" {
+                cli.top_level_only = true;
+                i += 1;
+                continue;
+            }
+            if a == "--success-only" {
+                cli.success_only = true;
+                i += 1;
+                continue;
+            }
+            if a == "--only-creator" {
+                cli.only_creator = true;
+                i += 1;
+                continue;
+            }
+            if a == "--approx-unique" {
+                cli.approx_unique = true;
+                i += 1;
+                continue;
+            }
+            if a == "--strict-mint" {
+                cli.strict_mint = true;
+                i += 1;
+                continue;
+            }
+            if a == "--include-accounts" {
+                cli.include_accounts = true;
+                i += 1;
+                continue;
+            }
+            if a == "--skip-errors" {
+                cli.skip_errors = true;
+                i += 1;
+                continue;
+            }
+            if a.starts_with('-') {
+                // Unknown flag, skip it and its possible value if in --flag=value form has no '='; best-effort skip only this token.
+                i += 1;
+                continue;
+            }
+            cli.positionals.push(a.clone());
+            i += 1;
+        }
+        cli.check_format()?;
+        Ok(cli)
+    }
+
+    /// Rejects modes whose events `--format` cannot carry: the protobuf and
+    /// bincode formats only encode trades, so anything else would be dropped
+    /// without a trace. `--routes` picks a format per event type instead.
+    fn check_format(&self) -> Result<(), Box<dyn Error>> {
+        let format = match self.format {
+            OutputFormat::Log => return Ok(()),
+            _ if self.routes.is_some() => return Ok(()),
+            OutputFormat::Protobuf => "protobuf",
+            OutputFormat::Bincode => "bincode",
+        };
+        let modes = [
+            (self.block_summary, "--block-summary"),
+            (self.net_positions, "--net-positions"),
+            (self.track_migrations, "--track-migrations"),
+            (self.track_holders, "--track-holders"),
+            (self.track_transfers, "--track-transfers"),
+            (self.vwap_window.is_some(), "--vwap-window"),
+            (self.breadth_window.is_some(), "--breadth-window"),
+        ];
+        match modes.iter().find(|(enabled, _)| *enabled) {
+            Some((_, flag)) => Err(format!(
+                "{flag} is not supported with --format {format}, which only carries trades; \
+                 use --format log or route its events with --routes"
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Consumes a flag that takes a value, accepting both `--flag value` and `--flag=value`.
+///
+/// Returns `Ok(None)` without advancing `i` when `args[*i]` is not one of `names`.
+fn take_value(
+    args: &[String],
+    i: &mut usize,
+    names: &[&str],
+) -> Result<Option<String>, Box<dyn Error>> {
+    let a = &args[*i];
+    for name in names {
+        if let Some(rest) = a.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            *i += 1;
+            return Ok(Some(rest.to_string()));
+        }
+        if a == name {
+            let value = args
+                .get(*i + 1)
+                .ok_or_else(|| format!("{} flag requires a value", names[0]))?;
+            *i += 2;
+            return Ok(Some(value.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Parses a duration such as `90`, `60s`, `5m` or `1h` into seconds.
+fn parse_duration_secs(value: &str) -> Result<u64, Box<dyn Error>> {
+    let value = value.trim();
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits_end);
+    let multiplier: u64 = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return Err(format!("invalid duration: {value}").into()),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {value}"))?;
+    match number.checked_mul(multiplier) {
+        Some(0) | None => Err(format!("invalid duration: {value}").into()),
+        Some(secs) => Ok(secs),
+    }
+}
+
+/// Parses a byte size such as `4096`, `512KB`, `100MB` or `2GB` (binary multiples).
+fn parse_size(value: &str) -> Result<u64, Box<dyn Error>> {
+    let upper = value.trim().to_ascii_uppercase();
+    let digits_end = upper
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(digits_end);
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("invalid size: {value}").into()),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {value}"))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size too large: {value}").into())
+}
+
+/// Parses a bonding curve progress given as a fraction (`0.9`) or a
+/// percentage (`90%`).
+fn parse_curve_progress(flag: &str, value: &str) -> Result<f64, Box<dyn Error>> {
+    let trimmed = value.trim();
+    let progress = match trimmed.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|percent| percent / 100.0),
+        None => trimmed.parse::<f64>(),
+    }
+    .map_err(|_| format!("invalid {flag} value: {value}"))?;
+    if !(0.0..=1.0).contains(&progress) {
+        return Err(format!("{flag} must be between 0 and 1 (or 0% and 100%), got {value}").into());
+    }
+    Ok(progress)
+}
+
+/// Serializes a secret as `"<redacted>"`, keeping only whether it was set.
+fn redact<S: Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    value.as_ref().map(|_| "<redacted>").serialize(serializer)
+}
+
+/// Serializes a URL with its query string, which often carries an API key,
+/// redacted.
+fn redact_query<S: Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    value
+        .as_deref()
+        .map(|url| match url.split_once('?') {
+            Some((base, _)) => format!("{base}?<redacted>"),
+            None => url.to_string(),
+        })
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, Box<dyn Error>> {
+        let args: Vec<String> = std::iter::once("pulstream")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        CliArgs::parse(&args)
+    }
+
+    #[test]
+    fn rejects_modes_trade_only_formats_cannot_carry() {
+        let flags: [&[&str]; 7] = [
+            &["--block-summary"],
+            &["--net-positions"],
+            &["--track-migrations"],
+            &["--track-holders"],
+            &["--track-transfers"],
+            &["--vwap-window", "60"],
+            &["--breadth-window", "10"],
+        ];
+        for format in ["protobuf", "bincode"] {
+            for flag in flags {
+                let mut args = vec!["--format", format];
+                args.extend_from_slice(flag);
+                let err = parse(&args).unwrap_err().to_string();
+                assert!(err.contains(flag[0]), "{err}");
+                assert!(err.contains(format), "{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn accepts_modes_with_log_format_or_routes() {
+        assert!(parse(&["--block-summary", "--track-holders"]).is_ok());
+        assert!(parse(&["--format", "log", "--net-positions"]).is_ok());
+        assert!(parse(&[
+            "--format",
+            "bincode",
+            "--routes",
+            "routes.toml",
+            "--track-transfers"
+        ])
+        .is_ok());
+        assert!(parse(&["--format", "protobuf"]).is_ok());
+    }
+
+    #[test]
+    fn require_db_takes_on_or_off() {
+        assert!(!parse(&[]).unwrap().db_optional);
+        assert!(!parse(&["--require-db", "on"]).unwrap().db_optional);
+        assert!(parse(&["--require-db=off"]).unwrap().db_optional);
+        assert!(parse(&["--require-db", "no"]).is_err());
+    }
+}
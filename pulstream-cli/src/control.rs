@@ -0,0 +1,94 @@
+//! The `--control-socket <path>` interface for pausing and resuming a long run
+//! without stopping the process.
+//!
+//! Each connection sends one command per line and gets one line back:
+//!
+//! - `pause`: stop processing transactions; those arriving are dropped
+//! - `resume`: process transactions again
+//! - `status`: `{"paused":..,"last_slot":..,"events":..,"paused_transactions":..}`
+//!
+//! For example `echo pause | nc -U /tmp/pulstream.sock`.
+
+use std::io;
+
+use pulstream_plugin::plugins::pumpfun_tracking::PumpfunTrackingPlugin;
+
+/// Binds `path` and serves commands for `plugin` on a background thread until
+/// the process exits. A stale socket file left by an earlier run is replaced.
+#[cfg(unix)]
+pub fn spawn(path: &str, plugin: PumpfunTrackingPlugin) -> io::Result<()> {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixListener,
+    };
+
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let listener = UnixListener::bind(path)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    log::warn!("Control socket accept failed: {}", err);
+                    continue;
+                }
+            };
+            let plugin = plugin.clone();
+            std::thread::spawn(move || {
+                let Ok(mut writer) = stream.try_clone() else {
+                    return;
+                };
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else {
+                        return;
+                    };
+                    let reply = handle(&plugin, line.trim());
+                    if writeln!(writer, "{reply}").is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn spawn(_path: &str, _plugin: PumpfunTrackingPlugin) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "control sockets are not supported on this platform",
+    ))
+}
+
+/// Runs one command and returns the reply line.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn handle(plugin: &PumpfunTrackingPlugin, command: &str) -> String {
+    match command {
+        "pause" => {
+            if !plugin.is_paused() {
+                log::info!("Paused via control socket");
+            }
+            plugin.pause();
+            "ok".to_string()
+        }
+        "resume" => {
+            if plugin.is_paused() {
+                log::info!("Resumed via control socket");
+            }
+            plugin.resume();
+            "ok".to_string()
+        }
+        "status" => format!(
+            "{{\"paused\":{},\"last_slot\":{},\"events\":{},\"paused_transactions\":{}}}",
+            plugin.is_paused(),
+            plugin.last_slot(),
+            plugin.events(),
+            plugin.paused_transactions()
+        ),
+        other => format!("error: unknown command {other:?}, expected pause, resume or status"),
+    }
+}
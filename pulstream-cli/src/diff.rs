@@ -0,0 +1,274 @@
+//! The `pulstream diff <a> <b>` subcommand, comparing the trade events of two
+//! runs, for example before and after a decoder upgrade.
+//!
+//! Each input is either a `--format bincode` output (`.bin`) or JSON lines of
+//! serialized [`OwnedTradeEvent`]s, such as a `--tcp` collector capture,
+//! whose run markers and other lines tagged with a `type` are skipped. Events are keyed by `(signature,
+//! instruction_path)`; an event only in `b` is an addition, one only in `a` a
+//! removal, and an event in both whose fields differ a change. The command
+//! prints a summary and the first `--limit` differences (10 by default), and
+//! fails if the runs differ.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use pulstream_plugin::{
+    plugins::pumpfun_tracking::OwnedTradeEvent, sinks::bincode::replay_bincode,
+};
+use serde_json::Value;
+
+/// Differences listed when `--limit` is not given.
+const DEFAULT_LIMIT: usize = 10;
+
+type EventKey = (String, String);
+
+/// One difference between the two runs.
+enum Difference {
+    Added(EventKey),
+    Removed(EventKey),
+    /// Fields that differ, with their value in `a` and in `b`.
+    Changed(EventKey, Vec<(String, Value, Value)>),
+}
+
+/// Runs `diff` with the arguments following the subcommand.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut paths = Vec::new();
+    let mut limit = DEFAULT_LIMIT;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--limit" | "-n" => {
+                let value = args.get(i + 1).ok_or("missing value for --limit")?;
+                limit = value
+                    .parse()
+                    .map_err(|_| format!("invalid --limit value: {value}"))?;
+                i += 2;
+            }
+            path => {
+                paths.push(path);
+                i += 1;
+            }
+        }
+    }
+    let [a, b] = paths[..] else {
+        return Err("usage: pulstream diff <a> <b> [--limit N]".into());
+    };
+
+    let (a_events, a_duplicates) = load_events(Path::new(a))?;
+    let (b_events, b_duplicates) = load_events(Path::new(b))?;
+    for (path, duplicates) in [(a, a_duplicates), (b, b_duplicates)] {
+        if duplicates > 0 {
+            eprintln!(
+                "{}: {} event(s) share a key with an earlier one; only the last is compared",
+                path, duplicates
+            );
+        }
+    }
+
+    let differences = compare(&a_events, &b_events)?;
+    let count = |pred: fn(&Difference) -> bool| differences.iter().filter(|d| pred(d)).count();
+    println!(
+        "{} events in {}, {} in {}: {} added, {} removed, {} changed",
+        a_events.len(),
+        a,
+        b_events.len(),
+        b,
+        count(|d| matches!(d, Difference::Added(_))),
+        count(|d| matches!(d, Difference::Removed(_))),
+        count(|d| matches!(d, Difference::Changed(..))),
+    );
+    for difference in differences.iter().take(limit) {
+        match difference {
+            Difference::Added((signature, path)) => println!("+ {signature} {path}"),
+            Difference::Removed((signature, path)) => println!("- {signature} {path}"),
+            Difference::Changed((signature, path), fields) => {
+                println!("~ {signature} {path}");
+                for (field, a_value, b_value) in fields {
+                    println!("    {field}: {a_value} -> {b_value}");
+                }
+            }
+        }
+    }
+    if differences.len() > limit {
+        println!("... {} more", differences.len() - limit);
+    }
+
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} difference(s) between {} and {}",
+            differences.len(),
+            a,
+            b
+        )
+        .into())
+    }
+}
+
+/// Loads the events of one run keyed by `(signature, instruction_path)`,
+/// returning them with the number of events whose key was already taken.
+fn load_events(path: &Path) -> Result<(BTreeMap<EventKey, Value>, u64), Box<dyn Error>> {
+    let file = BufReader::new(File::open(path)?);
+    let events: Vec<OwnedTradeEvent> = if path.extension().is_some_and(|ext| ext == "bin") {
+        replay_bincode(file).collect::<Result<_, _>>()?
+    } else {
+        read_json_lines(file, path)?
+    };
+
+    let mut keyed = BTreeMap::new();
+    let mut duplicates = 0;
+    for event in events {
+        let key = (event.signature.clone(), event.instruction_path.clone());
+        if keyed.insert(key, serde_json::to_value(&event)?).is_some() {
+            duplicates += 1;
+        }
+    }
+    Ok((keyed, duplicates))
+}
+
+/// Reads the trade events of JSON lines, skipping blank lines and lines
+/// tagged with a `type`, which are run markers rather than trades.
+fn read_json_lines(
+    reader: impl BufRead,
+    path: &Path,
+) -> Result<Vec<OwnedTradeEvent>, Box<dyn Error>> {
+    let mut events = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |err: serde_json::Error| {
+            format!("{}:{}: invalid event: {}", path.display(), number + 1, err)
+        };
+        let value: Value = serde_json::from_str(&line).map_err(invalid)?;
+        if value.get("type").is_some() {
+            continue;
+        }
+        events.push(serde_json::from_value(value).map_err(invalid)?);
+    }
+    Ok(events)
+}
+
+/// Lists the differences between two keyed runs, in key order.
+fn compare(
+    a: &BTreeMap<EventKey, Value>,
+    b: &BTreeMap<EventKey, Value>,
+) -> Result<Vec<Difference>, Box<dyn Error>> {
+    let mut differences = Vec::new();
+    for (key, a_event) in a {
+        let Some(b_event) = b.get(key) else {
+            differences.push(Difference::Removed(key.clone()));
+            continue;
+        };
+        let (Value::Object(a_fields), Value::Object(b_fields)) = (a_event, b_event) else {
+            return Err("events must serialize to objects".into());
+        };
+        let changed: Vec<_> = a_fields
+            .iter()
+            .filter_map(|(field, a_value)| {
+                let b_value = b_fields.get(field).unwrap_or(&Value::Null);
+                (a_value != b_value).then(|| (field.clone(), a_value.clone(), b_value.clone()))
+            })
+            .collect();
+        if !changed.is_empty() {
+            differences.push(Difference::Changed(key.clone(), changed));
+        }
+    }
+    differences.extend(
+        b.keys()
+            .filter(|key| !a.contains_key(*key))
+            .map(|key| Difference::Added(key.clone())),
+    );
+    differences.sort_by(|x, y| x.key().cmp(y.key()));
+    Ok(differences)
+}
+
+impl Difference {
+    fn key(&self) -> &EventKey {
+        match self {
+            Self::Added(key) | Self::Removed(key) | Self::Changed(key, _) => key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulstream_plugin::plugins::pumpfun_tracking::SCHEMA_VERSION;
+
+    fn event(signature: &str) -> OwnedTradeEvent {
+        OwnedTradeEvent {
+            schema_version: SCHEMA_VERSION,
+            signature: signature.to_string(),
+            slot: 250_000_000,
+            instruction_path: "2.0".to_string(),
+            timestamp: 1_700_000_000,
+            program_timestamp: 1_700_000_000,
+            ingest_time: None,
+            program_id: "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string(),
+            program_stack: Vec::new(),
+            mint: "2zMMhcVQEXDtdE6vsFS7S7D5oUodfJHE8vd1gnBouauv".to_string(),
+            payer: "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
+            amount_in: 250_000_000,
+            amount_out: 8_123_456_789_012,
+            is_buy: true,
+            priority_fee_micro_lamports: None,
+            accounts: Vec::new(),
+            symbol: None,
+            ewma_price: None,
+            curve_progress: None,
+            direction_mismatch: None,
+            block_time: None,
+            return_data: None,
+            processing_latency_ms: None,
+            sandwich_role: None,
+        }
+    }
+
+    #[test]
+    fn skips_run_markers_in_collector_captures() {
+        let capture = format!(
+            "{}\n{}\n\n{}\n{}\n",
+            r#"{"type":"run_started","schema_version":2,"mint":"m","start_slot":1,"end_slot":2}"#,
+            serde_json::to_string(&event("a")).unwrap(),
+            serde_json::to_string(&event("b")).unwrap(),
+            r#"{"type":"run_finished","mint":"m","start_slot":1,"end_slot":2,"last_slot":1,"events":2}"#,
+        );
+        let events = read_json_lines(capture.as_bytes(), Path::new("capture.jsonl")).unwrap();
+        assert_eq!(events, vec![event("a"), event("b")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_an_invalid_event() {
+        let err = read_json_lines("{\"slot\":1}\n".as_bytes(), Path::new("run.jsonl")).unwrap_err();
+        assert!(
+            err.to_string().starts_with("run.jsonl:1: invalid event"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn reports_changed_fields() {
+        let mut changed = event("a");
+        changed.amount_out += 1;
+        let keyed = |event: &OwnedTradeEvent| {
+            BTreeMap::from([(
+                (event.signature.clone(), event.instruction_path.clone()),
+                serde_json::to_value(event).unwrap(),
+            )])
+        };
+        let differences = compare(&keyed(&event("a")), &keyed(&changed)).unwrap();
+        let [Difference::Changed(_, fields)] = differences.as_slice() else {
+            panic!("expected one change");
+        };
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].0, "amount_out");
+    }
+}
@@ -0,0 +1,126 @@
+//! The `pulstream epochs --slots <start>:<end>` and `pulstream slots --epoch
+//! <N>` subcommands, converting between slot ranges and epochs for planning
+//! backfills without running anything.
+//!
+//! `--slots` is inclusive like the positional range unless `--exclusive-end`
+//! is given, and `--slots-per-epoch` selects the epoch length as it does for a
+//! run. Every printed slot range is shown in both the inclusive `<start>:<end>`
+//! form accepted by a run and the half-open `<start>..<end>` form.
+
+use std::{error::Error, ops::Range};
+
+use crate::range::{epoch_slot_range, resolve_slot_range, slot_epoch, MAINNET_SLOTS_PER_EPOCH};
+
+/// Options shared by both subcommands.
+struct Args {
+    slots: Option<String>,
+    epoch: Option<u64>,
+    exclusive_end: bool,
+    slots_per_epoch: u64,
+}
+
+/// Runs `epochs` with the arguments following the subcommand.
+pub fn run_epochs(args: &[String]) -> Result<(), Box<dyn Error>> {
+    const USAGE: &str =
+        "usage: pulstream epochs --slots <start>:<end> [--exclusive-end] [--slots-per-epoch N]";
+    let args = parse(args, USAGE)?;
+    let (Some(slots), None) = (args.slots.as_deref(), args.epoch) else {
+        return Err(USAGE.into());
+    };
+    if !slots.contains(':') {
+        return Err(format!("invalid --slots value: {slots}").into());
+    }
+    let range = resolve_slot_range(slots, args.exclusive_end, args.slots_per_epoch)
+        .map_err(|_| format!("invalid --slots value: {slots}"))?;
+    if range.is_empty() {
+        return Err(format!("empty slot range: {slots}").into());
+    }
+
+    let first = slot_epoch(range.start, args.slots_per_epoch)?;
+    let last = slot_epoch(range.end - 1, args.slots_per_epoch)?;
+    println!(
+        "slots {} span {} epoch(s): {}..={}",
+        forms(&range),
+        last - first + 1,
+        first,
+        last
+    );
+    for epoch in first..=last {
+        let epoch_range = epoch_slot_range(epoch, args.slots_per_epoch)?;
+        let covered = range.start.max(epoch_range.start)..range.end.min(epoch_range.end);
+        let coverage = if covered == epoch_range {
+            "full".to_string()
+        } else {
+            format!("partial: {}", forms(&covered))
+        };
+        println!(
+            "epoch {}: slots {}, {}",
+            epoch,
+            forms(&epoch_range),
+            coverage
+        );
+    }
+    Ok(())
+}
+
+/// Runs `slots` with the arguments following the subcommand.
+pub fn run_slots(args: &[String]) -> Result<(), Box<dyn Error>> {
+    const USAGE: &str = "usage: pulstream slots --epoch <N> [--slots-per-epoch N]";
+    let args = parse(args, USAGE)?;
+    let (Some(epoch), None) = (args.epoch, args.slots.as_deref()) else {
+        return Err(USAGE.into());
+    };
+    let range = epoch_slot_range(epoch, args.slots_per_epoch)?;
+    println!("epoch {}: slots {}", epoch, forms(&range));
+    Ok(())
+}
+
+fn parse(args: &[String], usage: &str) -> Result<Args, Box<dyn Error>> {
+    let mut parsed = Args {
+        slots: None,
+        epoch: None,
+        exclusive_end: false,
+        slots_per_epoch: MAINNET_SLOTS_PER_EPOCH,
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if flag == "--exclusive-end" {
+            parsed.exclusive_end = true;
+            i += 1;
+            continue;
+        }
+        let value = args
+            .get(i + 1)
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag {
+            "--slots" => parsed.slots = Some(value.clone()),
+            "--epoch" => {
+                parsed.epoch = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid --epoch value: {value}"))?,
+                )
+            }
+            "--slots-per-epoch" => {
+                parsed.slots_per_epoch = value
+                    .parse()
+                    .map_err(|_| format!("invalid --slots-per-epoch value: {value}"))?
+            }
+            _ => return Err(usage.into()),
+        }
+        i += 2;
+    }
+    Ok(parsed)
+}
+
+/// Formats a non-empty half-open range as `<start>:<last> (<start>..<end>)`.
+fn forms(range: &Range<u64>) -> String {
+    format!(
+        "{}:{} ({}..{})",
+        range.start,
+        range.end - 1,
+        range.start,
+        range.end
+    )
+}
@@ -0,0 +1,654 @@
+mod cli;
+mod control;
+mod diff;
+mod epochs;
+mod range;
+mod routes;
+
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use cli::{CliArgs, OutputFormat};
+use jetstreamer::{plugin::Plugin, JetstreamerRunner};
+use pulstream_plugin::{
+    filters::SuccessOnly,
+    plugins::{
+        pumpfun_tracking::{PumpfunTrackingPlugin, SCHEMA_VERSION},
+        timed::TimedPlugin,
+    },
+    sinks::{
+        bincode::BincodeSink,
+        delayed::DelayedSink,
+        log::LogSink,
+        null::NullSink,
+        output::{open_output, OutputOptions, RotationPolicy, SyncedFile},
+        protobuf::ProtobufSink,
+        split::{mint_path, SplitByMintSink},
+        tcp::TcpSink,
+        TradeSink,
+    },
+    utils::{
+        bundle::{replay_transaction, TransactionBundle},
+        dedup::DedupStore,
+        panic_context,
+    },
+};
+use range::{
+    merge_ranges, read_ranges, resolve_slot_range, reverse_chunks, MAINNET_SLOTS_PER_EPOCH,
+    REVERSE_CHUNK_SLOTS,
+};
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+use solana_pubkey_carbon::Pubkey as PubkeyCarbon;
+use solana_signature::Signature;
+use url::Url;
+
+/// RPC endpoint used for metadata lookups when none is configured.
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// Per-mint outputs kept open at once with `--split-by-mint`.
+const SPLIT_MAX_OPEN: usize = 64;
+
+/// Events queued for the `--tcp` collector while it is unreachable.
+const TCP_BUFFER_EVENTS: usize = 100_000;
+
+/// Seconds between `--status-file` updates when `--status-interval` is not given.
+const DEFAULT_STATUS_INTERVAL_SECS: u64 = 10;
+
+/// Configuration of a run after parsing, validation and defaults, printed with
+/// `--print-config` so a run can be reproduced exactly.
+#[derive(Serialize)]
+struct ResolvedConfig<'a> {
+    /// Version of the `pulstream` binary.
+    version: &'static str,
+    /// [`SCHEMA_VERSION`] of the trades written.
+    schema_version: u32,
+    mint: Option<&'a str>,
+    /// Slot ranges run in order, after merging and `--reverse` chunking.
+    slot_ranges: &'a [Range<u64>],
+    threads: usize,
+    slots_per_epoch: u64,
+    /// Destination of the main output, after `--output-dir` naming; `None`
+    /// with `--routes` or without a mint.
+    output: Option<&'a str>,
+    args: &'a CliArgs,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    panic_context::install_panic_hook();
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("diff") => return diff::run(&args[2..]),
+        Some("epochs") => return epochs::run_epochs(&args[2..]),
+        Some("slots") => return epochs::run_slots(&args[2..]),
+        _ => {}
+    }
+    let cli = CliArgs::parse(&args)?;
+
+    if cli.self_test {
+        let checked = PumpfunTrackingPlugin::new(Pubkey::default())
+            .self_test()
+            .map_err(|err| format!("decoder self-test failed: {err}"))?;
+        eprintln!("Decoder self-test passed ({} fixture(s))", checked);
+        // Without a range, the self-test is the whole run.
+        if cli.positionals.is_empty()
+            && cli.ranges_from.is_none()
+            && cli.replay_transaction.is_none()
+        {
+            return Ok(());
+        }
+    }
+
+    if let Some(mint) = cli.mint.as_deref() {
+        std::env::set_var("PULSTREAM_MINT", mint);
+        eprintln!("Configured mint: {}", mint);
+    }
+
+    let slots_per_epoch = cli.slots_per_epoch.unwrap_or(MAINNET_SLOTS_PER_EPOCH);
+    let slot_ranges = match cli.ranges_from.as_deref() {
+        // A replay processes one saved transaction instead of a slot range.
+        _ if cli.replay_transaction.is_some() => Vec::new(),
+        Some(source) => read_ranges_from(source, cli.exclusive_end, slots_per_epoch)?,
+        None => {
+            // Positional arguments are epochs or slot ranges.
+            if cli.positionals.is_empty() {
+                return Err("missing positional <epoch|start:end> argument".into());
+            }
+            // Unlike --ranges-from, a bad entry on the command line is a typo
+            // to fix rather than one line of a batch to skip.
+            cli.positionals
+                .iter()
+                .map(
+                    |arg| match resolve_slot_range(arg, cli.exclusive_end, slots_per_epoch) {
+                        Ok(range) if range.is_empty() => {
+                            Err(format!("slot range {arg} is empty").into())
+                        }
+                        Ok(range) => Ok(range),
+                        Err(err) => Err(format!(
+                            "invalid positional {arg:?}, expected <epoch|start:end>: {err}"
+                        )
+                        .into()),
+                    },
+                )
+                .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?
+        }
+    };
+    let (mut slot_ranges, overlap) = merge_ranges(slot_ranges);
+    if overlap > 0 {
+        eprintln!(
+            "Merged overlapping slot ranges; {} slot(s) covered twice are processed once",
+            overlap
+        );
+    }
+    // Span of all ranges, used to name `--output-dir` files.
+    let slot_range = slot_ranges
+        .iter()
+        .map(|range| range.start)
+        .min()
+        .unwrap_or(0)
+        ..slot_ranges.iter().map(|range| range.end).max().unwrap_or(0);
+
+    if cli.reverse {
+        slot_ranges = reverse_chunks(&slot_ranges, REVERSE_CHUNK_SLOTS);
+    }
+
+    let threads = std::env::var("JETSTREAMER_THREADS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(1);
+
+    if cli.strict_mint && cli.mint.is_none() {
+        return Err("--strict-mint requires --mint".into());
+    }
+    if cli.top_level_only {
+        check_top_level_only(&cli)?;
+    }
+    let mut plugin = None;
+    let mut null_sink = None;
+    let mut resolved_output = None;
+    // Breadth mode counts every mint and so runs without --mint; the default
+    // key is then only a placeholder, untracked via `with_track_mint(false)`.
+    let mint = cli
+        .mint
+        .clone()
+        .or_else(|| cli.breadth_window.map(|_| Pubkey::default().to_string()));
+    if let Some(mint) = mint.as_deref() {
+        let mint_pubkey = mint.parse::<Pubkey>()?;
+        if !cli.no_mint_check && cli.mint.is_some() {
+            check_mint_address(&mint_pubkey);
+        }
+        let output = match (cli.output.clone(), cli.output_dir.as_deref()) {
+            (Some(_), Some(_)) => {
+                return Err("--output and --output-dir are mutually exclusive".into())
+            }
+            (Some(output), None) => output,
+            (None, Some(dir)) => auto_output_path(dir, cli.format, mint, &slot_range)?,
+            (None, None) => "-".to_string(),
+        };
+        if cli.routes.is_none() {
+            resolved_output = Some(output.clone());
+        }
+        let output = output.as_str();
+        let output_options = OutputOptions {
+            rotation: RotationPolicy {
+                max_bytes: cli.rotate_size,
+                max_records: cli.rotate_events,
+            },
+            sync_interval: cli.sync_interval.map(Duration::from_secs),
+        };
+        let sink: Arc<dyn TradeSink> = if let Some(path) = cli.routes.as_deref() {
+            if cli.split_by_mint || cli.output.is_some() || cli.output_dir.is_some() {
+                return Err(
+                    "--routes configures its own outputs and cannot be combined with \
+                     --output, --output-dir or --split-by-mint"
+                        .into(),
+                );
+            }
+            Arc::new(routes::load(Path::new(path), output_options)?)
+        } else if cli.split_by_mint {
+            Arc::new(split_by_mint_sink(
+                &cli,
+                output,
+                output_options,
+                slot_range.clone(),
+            )?)
+        } else if output == "null" {
+            let sink = Arc::new(NullSink::default());
+            null_sink = Some(sink.clone());
+            sink
+        } else {
+            open_sink(cli.format, output, output_options)?
+        };
+        let sink: Arc<dyn TradeSink> = match cli.simulate_latency {
+            Some(millis) => {
+                eprintln!("--simulate-latency is for testing: every sink write sleeps {millis}ms");
+                Arc::new(DelayedSink::new(sink, Duration::from_millis(millis)))
+            }
+            None => sink,
+        };
+        if cli.net_positions && cli.block_summary {
+            return Err("--net-positions and --block-summary cannot be combined".into());
+        }
+        if let Some(key) = cli.hash_payers.as_deref() {
+            if key.is_empty() {
+                return Err("--hash-payers requires a non-empty key".into());
+            }
+            if cli.include_accounts || cli.dump_undecoded.is_some() || cli.archive_raw.is_some() {
+                return Err("--hash-payers cannot be combined with --include-accounts, \
+                     --dump-undecoded or --archive-raw, which write addresses in the clear"
+                    .into());
+            }
+        }
+        let mut tracking_plugin = PumpfunTrackingPlugin::new(mint_pubkey)
+            .with_sink(sink)
+            .with_skip_errors(cli.skip_errors)
+            .with_track_migrations(cli.track_migrations)
+            .with_track_holders(cli.track_holders)
+            .with_track_transfers(cli.track_transfers)
+            .with_block_time(cli.block_time)
+            .with_timestamp_source(cli.timestamp_source)
+            .with_block_summary(cli.block_summary)
+            .with_net_positions(cli.net_positions, cli.net_positions_max_payers)
+            .with_duplicate_slots(cli.on_duplicate)
+            .with_warmup_slots(slot_range.start, cli.warmup_slots)
+            .with_check_direction(cli.check_direction)
+            .with_detect_sandwiches(cli.detect_sandwiches)
+            .with_only_creator(cli.only_creator)
+            .with_top_level_only(cli.top_level_only)
+            .with_interarrival(cli.interarrival)
+            .with_stack_height_range(cli.min_stack_height, cli.max_stack_height)
+            .with_curve_progress_range(
+                cli.min_curve_progress,
+                cli.max_curve_progress,
+                cli.pass_missing_curve_progress,
+            )
+            .with_approx_unique(cli.approx_unique)
+            .with_strict_mint(cli.strict_mint)
+            .with_include_accounts(cli.include_accounts);
+        if !cli.fee_payers.is_empty() {
+            let fee_payers = cli
+                .fee_payers
+                .iter()
+                .map(|payer| payer.parse::<Pubkey>())
+                .collect::<Result<HashSet<_>, _>>()?;
+            tracking_plugin = tracking_plugin.with_fee_payer_filter(fee_payers);
+        }
+        if !cli.excluded_programs.is_empty() {
+            let programs = cli
+                .excluded_programs
+                .iter()
+                .map(|program| program.parse::<Pubkey>())
+                .collect::<Result<HashSet<_>, _>>()?;
+            tracking_plugin = tracking_plugin.with_excluded_programs(programs);
+        }
+        if let Some(addr) = cli.tcp.as_deref() {
+            let sink = TcpSink::connect(addr, TCP_BUFFER_EVENTS)
+                .map_err(|err| format!("failed to connect to collector {addr}: {err}"))?;
+            tracking_plugin = tracking_plugin.with_sink(Arc::new(sink));
+        }
+        if cli.success_only {
+            tracking_plugin = tracking_plugin.with_filter(SuccessOnly);
+        }
+        if cli.enrich_metadata {
+            let rpc_url = cli
+                .rpc_url
+                .clone()
+                .or_else(|| std::env::var("PULSTREAM_RPC_URL").ok())
+                .unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+            tracking_plugin = tracking_plugin.with_enrich_metadata(rpc_url);
+            if let Some(limit) = cli.enrich_concurrency {
+                tracking_plugin = tracking_plugin.with_enrich_concurrency(limit);
+            }
+        }
+        if let Some(key) = cli.hash_payers.as_deref() {
+            tracking_plugin = tracking_plugin.with_hash_payers(key);
+        }
+        if let Some(program) = cli.dump_undecoded.as_deref() {
+            tracking_plugin = tracking_plugin.with_dump_undecoded(program.parse::<Pubkey>()?);
+        }
+        if cli.verify_balances {
+            tracking_plugin = tracking_plugin.with_verify_balances(cli.balance_tolerance);
+        }
+        if let Some(max_bytes) = cli.max_instruction_data {
+            tracking_plugin = tracking_plugin
+                .with_max_instruction_data(usize::try_from(max_bytes).unwrap_or(usize::MAX));
+        }
+        if let Some(slots) = cli.breadth_window {
+            tracking_plugin = tracking_plugin
+                .with_breadth_window(slots)
+                .with_track_mint(cli.mint.is_some());
+        }
+        if let Some(n) = cli.top {
+            if cli.breadth_window.is_none() {
+                return Err("--top requires --breadth-window, which tracks every mint".into());
+            }
+            tracking_plugin = tracking_plugin.with_top_mints(n);
+        }
+        if let Some(secs) = cli.vwap_window {
+            tracking_plugin = tracking_plugin.with_vwap_window(Duration::from_secs(secs));
+        }
+        if let Some(secs) = cli.price_halflife {
+            tracking_plugin = tracking_plugin.with_price_half_life(Duration::from_secs(secs));
+        }
+        if let Some(path) = cli.dedup_store.as_deref() {
+            // Events of slots before this run cannot be emitted again.
+            let store = DedupStore::open(path, slot_range.start)?;
+            eprintln!("Dedup store {} holds {} event(s)", path, store.len());
+            tracking_plugin = tracking_plugin.with_dedup_store(store);
+        }
+        if let Some(path) = cli.status_file.as_deref() {
+            let secs = cli.status_interval.unwrap_or(DEFAULT_STATUS_INTERVAL_SECS);
+            tracking_plugin = tracking_plugin.with_status_file(path, Duration::from_secs(secs));
+        }
+        if let Some(secs) = cli.heartbeat_interval {
+            tracking_plugin = tracking_plugin.with_heartbeat_interval(Duration::from_secs(secs));
+        }
+        if let Some(signature) = cli.export_signature.as_deref() {
+            let path = cli
+                .export_out
+                .clone()
+                .unwrap_or_else(|| format!("{signature}.bundle"));
+            tracking_plugin =
+                tracking_plugin.with_export_transaction(signature.parse::<Signature>()?, path);
+        }
+        if let Some(path) = cli.archive_raw.as_deref() {
+            tracking_plugin =
+                tracking_plugin.with_raw_archive(open_output(path, OutputOptions::default())?);
+        }
+        plugin = Some(tracking_plugin);
+    }
+
+    if let Some(path) = &cli.print_config {
+        let config = ResolvedConfig {
+            version: env!("CARGO_PKG_VERSION"),
+            schema_version: SCHEMA_VERSION,
+            mint: mint.as_deref(),
+            slot_ranges: &slot_ranges,
+            threads,
+            slots_per_epoch,
+            output: resolved_output.as_deref(),
+            args: &cli,
+        };
+        let json = serde_json::to_string_pretty(&config)?;
+        match path {
+            Some(path) => std::fs::write(path, json + "\n")
+                .map_err(|err| format!("failed to write --print-config file {path}: {err}"))?,
+            None => eprintln!("{json}"),
+        }
+    }
+
+    if let Some(path) = cli.replay_transaction.as_deref() {
+        let plugin = plugin.ok_or("--replay-transaction requires --mint")?;
+        let transaction =
+            TransactionBundle::read_from(BufReader::new(File::open(path)?))?.into_transaction()?;
+        solana_logger::setup_with_default("info");
+        return replay_transaction(&plugin, &transaction)
+            .map_err(|err| -> Box<dyn std::error::Error> { err });
+    }
+
+    if let (Some(secs), Some(plugin)) = (cli.flush_interval, &plugin) {
+        spawn_flusher(plugin.clone(), Duration::from_secs(secs.max(1)));
+    }
+    if let Some(path) = cli.control_socket.as_deref() {
+        let plugin = plugin.clone().ok_or("--control-socket requires --mint")?;
+        control::spawn(path, plugin)
+            .map_err(|err| format!("failed to bind control socket {path}: {err}"))?;
+    }
+
+    // An empty DSN disables ClickHouse in the runner.
+    let clickhouse_url = match cli.clickhouse_url.as_deref().map(check_clickhouse_url) {
+        Some(Err(err)) if cli.db_optional => {
+            eprintln!("Ignoring invalid --clickhouse-url ({err}); running without ClickHouse");
+            Some(String::new())
+        }
+        Some(Err(err)) => return Err(format!("invalid --clickhouse-url: {err}").into()),
+        Some(Ok(())) => cli.clickhouse_url.clone(),
+        None => None,
+    };
+
+    let started = Instant::now();
+    let segments = slot_ranges.len();
+    for (index, range) in slot_ranges.into_iter().enumerate() {
+        let mut runner = JetstreamerRunner::default()
+            .with_log_level("info")
+            .with_threads(threads)
+            .with_slot_range(range);
+        if let Some(url) = &clickhouse_url {
+            runner = runner.with_clickhouse_dsn(url.clone());
+        }
+        if let Some(plugin) = &plugin {
+            // One run over every range: markers carry the span of all of
+            // them and are written once.
+            let plugin: Box<dyn Plugin> = Box::new(
+                plugin
+                    .clone()
+                    .with_slot_range(slot_range.clone())
+                    .with_run_segment(index == 0, index + 1 == segments),
+            );
+            runner = runner.with_plugin(if cli.profile {
+                Box::new(TimedPlugin::new(plugin))
+            } else {
+                plugin
+            });
+        }
+        runner
+            .run()
+            .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+    }
+
+    if let (true, Some(plugin)) = (cli.net_positions, &plugin) {
+        plugin.write_net_positions()?;
+        plugin.flush()?;
+    }
+
+    if let Some(sink) = &null_sink {
+        let elapsed = started.elapsed().as_secs_f64();
+        log::info!(
+            "Null sink dropped {} trade events in {:.1}s ({:.0} events/s)",
+            sink.events(),
+            elapsed,
+            sink.events() as f64 / elapsed.max(f64::EPSILON)
+        );
+    }
+
+    if let (true, Some(plugin)) = (cli.strict_mint, &plugin) {
+        if !plugin.mint_seen() {
+            return Err(format!(
+                "--strict-mint: mint {} was never seen in the processed range",
+                plugin.mint
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Flushes the plugin's sinks every `interval` until the process exits.
+///
+/// The runner has no periodic hook, so this runs on its own thread alongside it.
+fn spawn_flusher(plugin: PumpfunTrackingPlugin, interval: Duration) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        if let Err(err) = plugin.flush() {
+            log::warn!("Periodic flush failed: {}", err);
+        }
+    });
+}
+
+/// Reads the `--ranges-from` source (`-` for stdin, otherwise a file path).
+fn read_ranges_from(
+    source: &str,
+    exclusive_end: bool,
+    slots_per_epoch: u64,
+) -> Result<Vec<Range<u64>>, Box<dyn std::error::Error>> {
+    let reader: Box<dyn BufRead> = if source == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(source)?))
+    };
+    let ranges = read_ranges(reader, exclusive_end, slots_per_epoch)?;
+    if ranges.is_empty() {
+        return Err(format!("no valid slot ranges read from {source}").into());
+    }
+    Ok(ranges)
+}
+
+/// Warns if `mint` is off the ed25519 curve, i.e. a program-derived address.
+///
+/// Pumpfun mints are generated keypairs and so lie on the curve, while bonding
+/// curves and pools are PDAs. This is a heuristic, so it only warns.
+/// Rejects `--top-level-only` unless it is asked for events it can see.
+/// Pumpfun logs trades and migrations as self-CPI inner instructions, which
+/// the mode skips, so only new holders, transfers and undecoded top-level
+/// instructions come out; a run relying on trades would silently be empty.
+fn check_top_level_only(cli: &CliArgs) -> Result<(), String> {
+    let trade_modes = [
+        (cli.track_migrations, "--track-migrations"),
+        (cli.block_summary, "--block-summary"),
+        (cli.net_positions, "--net-positions"),
+        (cli.detect_sandwiches, "--detect-sandwiches"),
+        (cli.breadth_window.is_some(), "--breadth-window"),
+        (cli.vwap_window.is_some(), "--vwap-window"),
+    ];
+    if let Some((_, flag)) = trade_modes.iter().find(|(set, _)| *set) {
+        return Err(format!(
+            "--top-level-only skips the inner instructions pumpfun logs trades and \
+             migrations in, so {flag} would see nothing"
+        ));
+    }
+    if !cli.track_holders && !cli.track_transfers && cli.dump_undecoded.is_none() {
+        return Err(
+            "--top-level-only skips the inner instructions pumpfun logs trades in; \
+             use it with --track-holders, --track-transfers or --dump-undecoded"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+fn check_mint_address(mint: &Pubkey) {
+    if !PubkeyCarbon::from(mint.to_bytes()).is_on_curve() {
+        log::warn!(
+            "{} is a program-derived address, not a typical token mint; did you pass a bonding curve or pool address instead of the mint?",
+            mint
+        );
+    }
+}
+
+/// Checks a ClickHouse DSN before any slot is processed.
+///
+/// The runner builds its client from the URL alone and replaces its query
+/// string, so credentials or settings in the URL would be silently dropped;
+/// they are rejected instead. The runner's client is not reachable from
+/// here, so they cannot be applied as its user and password either.
+fn check_clickhouse_url(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "unsupported scheme {}, expected http or https",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err("missing host".to_string());
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("credentials in the URL are not supported by the runner's client".to_string());
+    }
+    if url.query().is_some() {
+        return Err("query parameters would be replaced by the runner's client".to_string());
+    }
+    Ok(())
+}
+
+/// Opens the sink writing `format` to `output`.
+fn open_sink(
+    format: OutputFormat,
+    output: &str,
+    options: OutputOptions,
+) -> io::Result<Arc<dyn TradeSink>> {
+    Ok(match format {
+        _ if output == "null" => Arc::new(NullSink::default()),
+        OutputFormat::Log => Arc::new(LogSink),
+        OutputFormat::Protobuf => Arc::new(ProtobufSink::open(output, options)?),
+        OutputFormat::Bincode => Arc::new(BincodeSink::open(output, options)?),
+    })
+}
+
+/// Builds the `--split-by-mint` sink. Each mint writes to `<stem>.<mint>.<ext>`
+/// next to `--output`, or to its own automatically named file in
+/// `--output-dir`.
+fn split_by_mint_sink(
+    cli: &CliArgs,
+    output: &str,
+    options: OutputOptions,
+    slot_range: Range<u64>,
+) -> Result<SplitByMintSink, Box<dyn std::error::Error>> {
+    if cli.format.extension().is_none() {
+        return Err(
+            "--split-by-mint requires a file output format such as --format protobuf".into(),
+        );
+    }
+    if output == "-" || output == "null" || output.contains(':') {
+        return Err(format!("--split-by-mint requires a file output, got {output}").into());
+    }
+    // A closed output is reopened in append mode, which rotation cannot resume.
+    if options.rotation.is_enabled() {
+        return Err("--split-by-mint cannot be combined with output rotation".into());
+    }
+    let format = cli.format;
+    let output_dir = cli.output_dir.clone();
+    let base = Path::new(output).to_path_buf();
+    let open = move |mint: &str, reopen: bool| -> io::Result<Box<dyn TradeSink>> {
+        let path = match output_dir.as_deref() {
+            Some(dir) => auto_output_path(dir, format, mint, &slot_range)
+                .map_err(|err| io::Error::other(err.to_string()))?
+                .into(),
+            None => mint_path(&base, mint),
+        };
+        let file = if reopen {
+            SyncedFile::append(&path, options.sync_interval)?
+        } else {
+            SyncedFile::create(&path, options.sync_interval)?
+        };
+        let writer: Box<dyn io::Write + Send> = Box::new(file);
+        Ok(match format {
+            OutputFormat::Bincode => Box::new(BincodeSink::new(writer)),
+            _ => Box::new(ProtobufSink::new(writer)),
+        })
+    };
+    Ok(SplitByMintSink::new(Box::new(open), SPLIT_MAX_OPEN))
+}
+
+/// Builds `<dir>/trades-<mint>-<start>-<end>.<ext>` for `--output-dir`, creating
+/// `dir` if needed. `<end>` is the last slot processed.
+fn auto_output_path(
+    dir: &str,
+    format: OutputFormat,
+    mint: &str,
+    slot_range: &Range<u64>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let extension = format
+        .extension()
+        .ok_or("--output-dir requires a file output format such as --format protobuf")?;
+    std::fs::create_dir_all(dir)?;
+    let file_name = format!(
+        "trades-{}-{}-{}.{}",
+        mint,
+        slot_range.start,
+        slot_range.end.saturating_sub(1),
+        extension
+    );
+    Ok(Path::new(dir)
+        .join(file_name)
+        .to_string_lossy()
+        .into_owned())
+}
@@ -0,0 +1,247 @@
+//! Resolution of the positional `<epoch|start:end>` argument into a slot range.
+//!
+//! The runner consumes half-open ranges (`start..end`). By default the end of an
+//! explicit `<start>:<end>` range is inclusive, so `100:200` processes slot 200;
+//! with `--exclusive-end` it stops at slot 199. An epoch argument always covers
+//! every slot of that epoch, so both modes resolve it to the same range.
+//!
+//! Epochs are resolved with mainnet's 432,000 slots per epoch unless
+//! `--slots-per-epoch` overrides it for a cluster with a different schedule.
+//!
+//! Every range is half-open once resolved, and epoch `N` ends exactly where
+//! epoch `N + 1` starts. When several ranges are given, [`merge_ranges`] joins
+//! any that overlap, so adjacent epochs, or inclusive ranges sharing an end
+//! slot such as `100:200` and `200:300`, process each slot once.
+
+use std::{cmp::Reverse, error::Error, io::BufRead, ops::Range};
+
+use jetstreamer::firehose::epochs;
+
+/// Slots per epoch on mainnet-beta, matching [`epochs::epoch_to_slot_range`].
+pub const MAINNET_SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// Resolves an epoch number or `<start>:<end>` slot range into a half-open range.
+pub fn resolve_slot_range(
+    arg: &str,
+    exclusive_end: bool,
+    slots_per_epoch: u64,
+) -> Result<Range<u64>, Box<dyn Error>> {
+    if arg.contains(':') {
+        let (slot_a, slot_b) = arg
+            .split_once(':')
+            .ok_or("failed to parse slot range, expected <start>:<end>")?;
+        let slot_a: u64 = slot_a.parse()?;
+        let slot_b: u64 = slot_b.parse()?;
+        if exclusive_end {
+            Ok(slot_a..slot_b)
+        } else {
+            Ok(slot_a..(slot_b + 1))
+        }
+    } else {
+        let epoch: u64 = arg.parse()?;
+        epoch_slot_range(epoch, slots_per_epoch)
+    }
+}
+
+/// Returns the half-open slot range covering `epoch`.
+///
+/// [`epochs::epoch_to_slot_range`] reports the last slot inclusively, so the
+/// exclusive end is one past it regardless of `--exclusive-end`. Other epoch
+/// lengths assume a fixed schedule without warmup epochs.
+pub fn epoch_slot_range(epoch: u64, slots_per_epoch: u64) -> Result<Range<u64>, Box<dyn Error>> {
+    if slots_per_epoch == MAINNET_SLOTS_PER_EPOCH {
+        let (start_slot, end_slot_inclusive) = epochs::epoch_to_slot_range(epoch);
+        return Ok(start_slot..(end_slot_inclusive + 1));
+    }
+    if slots_per_epoch == 0 {
+        return Err("slots per epoch must be greater than zero".into());
+    }
+    let start_slot = epoch
+        .checked_mul(slots_per_epoch)
+        .ok_or("epoch is out of range for the configured slots per epoch")?;
+    let end_slot = start_slot
+        .checked_add(slots_per_epoch)
+        .ok_or("epoch is out of range for the configured slots per epoch")?;
+    Ok(start_slot..end_slot)
+}
+
+/// Reads newline-delimited epochs or `<start>:<end>` ranges, one per line.
+///
+/// Blank lines and lines starting with `#` are ignored. Malformed lines are
+/// reported on stderr with their line number and skipped, so one bad entry does
+/// not abort a batch.
+pub fn read_ranges(
+    reader: impl BufRead,
+    exclusive_end: bool,
+    slots_per_epoch: u64,
+) -> Result<Vec<Range<u64>>, Box<dyn Error>> {
+    let mut ranges = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match resolve_slot_range(line, exclusive_end, slots_per_epoch) {
+            Ok(range) if range.is_empty() => {
+                eprintln!("Skipping empty slot range on line {}: {}", index + 1, line)
+            }
+            Ok(range) => ranges.push(range),
+            Err(err) => eprintln!(
+                "Skipping malformed slot range on line {}: {} ({})",
+                index + 1,
+                line,
+                err
+            ),
+        }
+    }
+    Ok(ranges)
+}
+
+/// Sorts `ranges` and joins those that overlap or touch, so that no slot
+/// appears in more than one range. Returns the merged ranges and the number of
+/// slots that were covered more than once.
+pub fn merge_ranges(mut ranges: Vec<Range<u64>>) -> (Vec<Range<u64>>, u64) {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    let mut overlap = 0;
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                overlap += last.end.min(range.end) - range.start;
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    (merged, overlap)
+}
+
+/// Slots per chunk when processing in reverse, about 11 hours of chain and a
+/// quarter of an epoch. Every chunk is a run of its own, with a fresh runner,
+/// runtime and, for a local ClickHouse, a ClickHouse process, so chunks are
+/// kept large enough for that startup to stay small next to streaming them.
+pub const REVERSE_CHUNK_SLOTS: u64 = 100_000;
+
+/// Splits `ranges` into chunks of at most `chunk_slots` slots and orders them
+/// from the highest slot to the lowest.
+///
+/// The firehose only streams forward, so slots within a chunk are still
+/// processed in ascending order; only the chunks run newest first.
+pub fn reverse_chunks(ranges: &[Range<u64>], chunk_slots: u64) -> Vec<Range<u64>> {
+    let chunk_slots = chunk_slots.max(1);
+    let mut chunks = Vec::new();
+    for range in ranges {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(chunk_slots).min(range.end);
+            chunks.push(start..end);
+            start = end;
+        }
+    }
+    chunks.sort_by_key(|chunk| Reverse(chunk.start));
+    chunks
+}
+
+/// Returns the epoch containing `slot`, the inverse of [`epoch_slot_range`].
+pub fn slot_epoch(slot: u64, slots_per_epoch: u64) -> Result<u64, Box<dyn Error>> {
+    if slots_per_epoch == MAINNET_SLOTS_PER_EPOCH {
+        return Ok(epochs::slot_to_epoch(slot));
+    }
+    if slots_per_epoch == 0 {
+        return Err("slots per epoch must be greater than zero".into());
+    }
+    Ok(slot / slots_per_epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_ranges_respect_the_end_mode() {
+        assert_eq!(
+            resolve_slot_range("100:200", false, MAINNET_SLOTS_PER_EPOCH).unwrap(),
+            100..201
+        );
+        assert_eq!(
+            resolve_slot_range("100:200", true, MAINNET_SLOTS_PER_EPOCH).unwrap(),
+            100..200
+        );
+        assert!(resolve_slot_range("100:100", true, MAINNET_SLOTS_PER_EPOCH)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn epochs_cover_the_whole_epoch_in_both_modes() {
+        for exclusive_end in [false, true] {
+            assert_eq!(
+                resolve_slot_range("2", exclusive_end, MAINNET_SLOTS_PER_EPOCH).unwrap(),
+                864_000..1_296_000
+            );
+            assert_eq!(
+                resolve_slot_range("2", exclusive_end, 100).unwrap(),
+                200..300
+            );
+        }
+    }
+
+    #[test]
+    fn consecutive_epochs_meet() {
+        for slots_per_epoch in [MAINNET_SLOTS_PER_EPOCH, 8_192] {
+            let first = epoch_slot_range(7, slots_per_epoch).unwrap();
+            let next = epoch_slot_range(8, slots_per_epoch).unwrap();
+            assert_eq!(first.end, next.start);
+            assert_eq!(first.end - first.start, slots_per_epoch);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        assert!(resolve_slot_range("100:", false, MAINNET_SLOTS_PER_EPOCH).is_err());
+        assert!(resolve_slot_range("abc", false, MAINNET_SLOTS_PER_EPOCH).is_err());
+        assert!(epoch_slot_range(1, 0).is_err());
+        assert!(epoch_slot_range(u64::MAX, 2).is_err());
+    }
+
+    /// Every slot of `ranges`, in processing order.
+    fn slots(ranges: &[Range<u64>]) -> Vec<u64> {
+        ranges.iter().flat_map(|range| range.clone()).collect()
+    }
+
+    #[test]
+    fn adjacent_epochs_process_each_slot_once() {
+        let epochs = vec![
+            epoch_slot_range(501, 100).unwrap(),
+            epoch_slot_range(500, 100).unwrap(),
+        ];
+        let (merged, overlap) = merge_ranges(epochs);
+        assert_eq!(merged, vec![50_000..50_200; 1]);
+        assert_eq!(overlap, 0);
+        assert_eq!(slots(&merged), (50_000..50_200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn inclusive_ranges_sharing_an_end_slot_process_it_once() {
+        let ranges = ["100:200", "200:300"]
+            .iter()
+            .map(|arg| resolve_slot_range(arg, false, MAINNET_SLOTS_PER_EPOCH).unwrap())
+            .collect();
+        let (merged, overlap) = merge_ranges(ranges);
+        assert_eq!(merged, vec![100..301; 1]);
+        assert_eq!(overlap, 1);
+        let slots = slots(&merged);
+        assert_eq!(slots.iter().filter(|&&slot| slot == 200).count(), 1);
+        assert_eq!(slots.len(), 201);
+    }
+
+    #[test]
+    fn merge_counts_overlap_and_keeps_gaps() {
+        let (merged, overlap) = merge_ranges(vec![10..20, 0..5, 15..30, 40..40, 12..18]);
+        assert_eq!(merged, [0..5, 10..30]);
+        // 15..20 and 12..18 were already covered.
+        assert_eq!(overlap, 5 + 6);
+    }
+}
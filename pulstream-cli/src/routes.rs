@@ -0,0 +1,80 @@
+//! The `--routes <file>` routing configuration, sending each event type to its
+//! own sink instead of the single `--format`/`--output` sink.
+//!
+//! ```toml
+//! # Optional: sink for event types without a route.
+//! fallback = "log"
+//!
+//! [sinks.trades]
+//! format = "protobuf"
+//! output = "trades.pb"
+//!
+//! [sinks.log]
+//! format = "log"
+//!
+//! [routes]
+//! trade = "trades"
+//! migration = "log"
+//! ```
+//!
+//! Each sink takes a `format` as in `--format` and an `output` as in
+//! `--output` (stdout by default). Routes map an event type (see
+//! [`EventKind`]) to a sink name. Sink names and event types are checked when
+//! the file is loaded, before any slot is processed.
+
+use std::{collections::HashMap, error::Error, path::Path, sync::Arc};
+
+use pulstream_plugin::sinks::{
+    output::OutputOptions,
+    router::{EventKind, RoutingSink},
+    TradeSink,
+};
+use serde::Deserialize;
+
+use crate::{cli::OutputFormat, open_sink};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoutingConfig {
+    fallback: Option<String>,
+    sinks: HashMap<String, SinkConfig>,
+    #[serde(default)]
+    routes: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SinkConfig {
+    format: String,
+    output: Option<String>,
+}
+
+/// Reads the routing file at `path` and opens its sinks, applying `options`
+/// to file outputs.
+pub fn load(path: &Path, options: OutputOptions) -> Result<RoutingSink, Box<dyn Error>> {
+    let config: RoutingConfig = toml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let mut routes = HashMap::new();
+    for (kind, sink) in config.routes {
+        let kind = kind
+            .parse::<EventKind>()
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        routes.insert(kind, sink);
+    }
+
+    let mut sinks: HashMap<String, Arc<dyn TradeSink>> = HashMap::new();
+    for (name, sink) in config.sinks {
+        let format = sink
+            .format
+            .parse::<OutputFormat>()
+            .map_err(|err| format!("{}: sink {:?}: {}", path.display(), name, err))?;
+        let output = sink.output.as_deref().unwrap_or("-");
+        let opened = open_sink(format, output, options)
+            .map_err(|err| format!("{}: sink {:?}: {}", path.display(), name, err))?;
+        sinks.insert(name, opened);
+    }
+
+    RoutingSink::new(sinks, routes, config.fallback.as_deref())
+        .map_err(|err| format!("{}: {}", path.display(), err).into())
+}
@@ -0,0 +1,38 @@
+//! Timing loop shared by the benchmarks. They are plain binaries
+//! (`harness = false`) so they build without extra dependencies:
+//!
+//! ```bash
+//! cargo bench -p pulstream-plugin
+//! ```
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// Runs `f` for a short warmup, then for at least a second, and prints the
+/// mean time per call under `name`. Returns that mean.
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    let warmup = Instant::now();
+    while warmup.elapsed() < Duration::from_millis(200) {
+        black_box(f());
+    }
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        iterations += 1;
+    }
+    let mean = start.elapsed() / iterations;
+    println!("{name:<48} {:>12.3} µs/iter", mean.as_secs_f64() * 1e6);
+    mean
+}
+
+/// Prints how many times faster `candidate` ran than `baseline`.
+pub fn speedup(baseline: Duration, candidate: Duration) {
+    println!(
+        "{:<48} {:>12.2}x",
+        "speedup",
+        baseline.as_secs_f64() / candidate.as_secs_f64()
+    );
+}
@@ -0,0 +1,72 @@
+//! Decoding every instruction of a dense pumpfun transaction against checking
+//! the trade event discriminator first, as the plugin does before calling the
+//! decoder.
+
+mod common;
+
+use carbon_core::{deserialize::CarbonDeserialize, instruction::InstructionDecoder};
+use carbon_pumpfun_decoder::{instructions::trade_event::TradeEvent, PumpfunDecoder};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey_carbon::Pubkey;
+use std::hint::black_box;
+
+const PUMPFUN: Pubkey =
+    solana_pubkey_carbon::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+/// Length of a trade event's data, discriminator included.
+const TRADE_EVENT_LEN: usize = 16 + 250;
+
+/// Instructions of a router transaction making `trades` pumpfun buys: each a
+/// compute-budget-like instruction of another program, the buy, a token
+/// transfer CPI and the logged trade event.
+fn dense_transaction(trades: usize) -> Vec<Instruction> {
+    let accounts: Vec<AccountMeta> = (0..16)
+        .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+        .collect();
+    let other = Pubkey::new_unique();
+    let mut buy = BUY_DISCRIMINATOR.to_vec();
+    buy.extend_from_slice(&[1; 16]);
+    let mut event = TradeEvent::DISCRIMINATOR.to_vec();
+    event.resize(TRADE_EVENT_LEN, 0);
+    (0..trades)
+        .flat_map(|_| {
+            [
+                Instruction::new_with_bytes(other, &[2, 0, 0, 0, 0], vec![]),
+                Instruction::new_with_bytes(PUMPFUN, &buy, accounts.clone()),
+                Instruction::new_with_bytes(other, &[3; 9], accounts[..3].to_vec()),
+                Instruction::new_with_bytes(PUMPFUN, &event, accounts[..1].to_vec()),
+            ]
+        })
+        .collect()
+}
+
+fn main() {
+    let decoder = PumpfunDecoder;
+    for trades in [4, 16, 64] {
+        let instructions = dense_transaction(trades);
+        let decode_all = || {
+            instructions
+                .iter()
+                .filter_map(|instruction| decoder.decode_instruction(black_box(instruction)))
+                .count()
+        };
+        let prefiltered = || {
+            instructions
+                .iter()
+                .filter(|instruction| instruction.data.starts_with(TradeEvent::DISCRIMINATOR))
+                .filter_map(|instruction| decoder.decode_instruction(black_box(instruction)))
+                .count()
+        };
+
+        assert_eq!(decode_all(), prefiltered());
+
+        println!(
+            "{} instructions, {} trade events",
+            instructions.len(),
+            trades
+        );
+        let baseline = common::bench("decode every instruction", decode_all);
+        let candidate = common::bench("discriminator pre-filter", prefiltered);
+        common::speedup(baseline, candidate);
+    }
+}
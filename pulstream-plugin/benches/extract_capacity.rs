@@ -0,0 +1,89 @@
+//! Growth of the extracted instruction list of instruction-heavy
+//! transactions: filling it from `Vec::with_capacity(32)`, as extraction used
+//! to, against the exact capacity `extract_instructions_with_metadata` now
+//! computes from the message and its inner instructions.
+
+mod common;
+
+use pulstream_plugin::utils::{
+    instruction::InstructionsWithMetadata, transformers::extract_instructions_with_metadata,
+};
+use solana_hash::Hash;
+use solana_message::{
+    compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader, VersionedMessage,
+};
+use solana_pubkey::Pubkey;
+use solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta};
+use std::{hint::black_box, sync::Arc};
+
+/// The fixed capacity extraction started from before it was presized.
+const FIXED_CAPACITY: usize = 32;
+
+fn compiled() -> CompiledInstruction {
+    CompiledInstruction {
+        program_id_index: 8,
+        accounts: (0..8).collect(),
+        data: vec![7; 24],
+    }
+}
+
+/// A transaction of `top_level` instructions, each with `cpis` direct inner
+/// instructions.
+fn transaction(top_level: usize, cpis: usize) -> (VersionedMessage, TransactionStatusMeta) {
+    let message = VersionedMessage::Legacy(Message {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        },
+        account_keys: (0..9).map(|_| Pubkey::new_unique()).collect(),
+        recent_blockhash: Hash::default(),
+        instructions: (0..top_level).map(|_| compiled()).collect(),
+    });
+    let inner = (0..top_level)
+        .map(|index| InnerInstructions {
+            index: index as u8,
+            instructions: (0..cpis)
+                .map(|_| InnerInstruction {
+                    instruction: compiled(),
+                    stack_height: Some(2),
+                })
+                .collect(),
+        })
+        .collect();
+    let meta = TransactionStatusMeta {
+        inner_instructions: Some(inner),
+        ..TransactionStatusMeta::default()
+    };
+    (message, meta)
+}
+
+fn main() {
+    let transaction_metadata = Arc::default();
+    for (top_level, cpis) in [(8, 3), (32, 7), (64, 15)] {
+        let (message, meta) = transaction(top_level, cpis);
+        let extracted = extract_instructions_with_metadata(&transaction_metadata, &message, &meta);
+        let exact = extracted.len();
+        assert_eq!(exact, top_level * (cpis + 1));
+        let fill = |capacity: usize| {
+            let mut instructions: InstructionsWithMetadata = Vec::with_capacity(capacity);
+            // One push per instruction, as extraction does.
+            for instruction in black_box(&extracted) {
+                instructions.push(instruction.clone());
+            }
+            instructions
+        };
+
+        println!("{exact} instructions");
+        common::bench("extract_instructions_with_metadata", || {
+            extract_instructions_with_metadata(
+                &transaction_metadata,
+                black_box(&message),
+                black_box(&meta),
+            )
+        });
+        let baseline = common::bench("fill from with_capacity(32)", || fill(FIXED_CAPACITY));
+        let candidate = common::bench("fill from exact capacity", || fill(exact));
+        common::speedup(baseline, candidate);
+    }
+}
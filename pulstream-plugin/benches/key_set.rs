@@ -0,0 +1,66 @@
+//! Membership tests of transaction account keys against fee payer or program
+//! sets of growing size: `Vec::contains`, `HashSet::contains` and [`KeySet`],
+//! which puts a Bloom filter in front of the `HashSet` above
+//! [`BLOOM_THRESHOLD`] keys.
+
+mod common;
+
+use pulstream_plugin::utils::bloom::{BloomFilter, KeySet, BLOOM_THRESHOLD};
+use solana_pubkey::Pubkey;
+use std::{collections::HashSet, hint::black_box};
+
+/// Account keys looked up per iteration, as in a few hundred transactions.
+const LOOKUPS: usize = 4096;
+/// One lookup in this many is a member, the rest are misses as most account
+/// keys are.
+const HIT_EVERY: usize = 100;
+/// `Vec::contains` is only timed up to this size, beyond which it takes
+/// seconds per iteration.
+const MAX_VEC: usize = 10_000;
+
+fn main() {
+    for size in [16, BLOOM_THRESHOLD, 10_000, 100_000] {
+        let members: Vec<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+        let lookups: Vec<Pubkey> = (0..LOOKUPS)
+            .map(|i| {
+                if i % HIT_EVERY == 0 {
+                    members[i % size]
+                } else {
+                    Pubkey::new_unique()
+                }
+            })
+            .collect();
+        let hash_set: HashSet<Pubkey> = members.iter().copied().collect();
+        let key_set = KeySet::new(hash_set.clone());
+        let hits = LOOKUPS.div_ceil(HIT_EVERY);
+
+        let mut bloom = BloomFilter::with_capacity(size);
+        for key in &members {
+            bloom.insert(&key.to_bytes());
+        }
+        let false_positives = lookups
+            .iter()
+            .filter(|key| bloom.might_contain(&key.to_bytes()) && !hash_set.contains(key))
+            .count();
+
+        let count = |contains: &dyn Fn(&Pubkey) -> bool| {
+            lookups
+                .iter()
+                .filter(|key| contains(black_box(key)))
+                .count()
+        };
+        assert_eq!(count(&|key| hash_set.contains(key)), hits);
+        assert_eq!(count(&|key| key_set.contains(key)), hits);
+
+        println!(
+            "{size} keys, {LOOKUPS} lookups, Bloom filter false positive rate {:.2}%",
+            100.0 * false_positives as f64 / (LOOKUPS - hits) as f64
+        );
+        if size <= MAX_VEC {
+            common::bench("Vec::contains", || count(&|key| members.contains(key)));
+        }
+        let baseline = common::bench("HashSet::contains", || count(&|key| hash_set.contains(key)));
+        let candidate = common::bench("KeySet::contains", || count(&|key| key_set.contains(key)));
+        common::speedup(baseline, candidate);
+    }
+}
@@ -0,0 +1,113 @@
+//! Running several detectors over one extraction of a transaction's
+//! instruction tree, as the plugin does, against extracting and walking the
+//! tree once per detector.
+
+mod common;
+
+use pulstream_plugin::utils::{
+    instruction::{NestedInstruction, NestedInstructions},
+    transformers::extract_instructions_with_metadata,
+};
+use solana_hash::Hash;
+use solana_instruction::Instruction;
+use solana_message::{
+    compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader, VersionedMessage,
+};
+use solana_pubkey::Pubkey;
+use solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta};
+use std::{hint::black_box, sync::Arc};
+
+fn compiled(program_id_index: u8) -> CompiledInstruction {
+    CompiledInstruction {
+        program_id_index,
+        accounts: (0..12).collect(),
+        data: vec![7; 40],
+    }
+}
+
+/// A transaction of `top_level` instructions, each invoking `cpis` inner
+/// instructions that cycle through stack heights 2 to 5 and programs 12 to 15.
+fn transaction(top_level: usize, cpis: usize) -> (VersionedMessage, TransactionStatusMeta) {
+    let message = VersionedMessage::Legacy(Message {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 4,
+        },
+        account_keys: (0..16).map(|_| Pubkey::new_unique()).collect(),
+        recent_blockhash: Hash::default(),
+        instructions: (0..top_level).map(|_| compiled(12)).collect(),
+    });
+    let inner = (0..top_level)
+        .map(|index| InnerInstructions {
+            index: index as u8,
+            instructions: (0..cpis)
+                .map(|cpi| InnerInstruction {
+                    instruction: compiled(12 + (cpi % 4) as u8),
+                    stack_height: Some(2 + (cpi % 4) as u32),
+                })
+                .collect(),
+        })
+        .collect();
+    let meta = TransactionStatusMeta {
+        inner_instructions: Some(inner),
+        ..TransactionStatusMeta::default()
+    };
+    (message, meta)
+}
+
+/// Visits every instruction of `instructions`, depth first.
+fn walk(instructions: &[NestedInstruction], visit: &mut impl FnMut(&Instruction)) {
+    for nested in instructions {
+        visit(&nested.instruction);
+        walk(&nested.inner_instructions, visit);
+    }
+}
+
+fn main() {
+    let transaction_metadata = Arc::default();
+    for (top_level, cpis) in [(4, 16), (8, 64), (16, 200)] {
+        let (message, meta) = transaction(top_level, cpis);
+        // Stand-ins for the trade, new holder and transfer detectors, each
+        // matching the instructions of one program.
+        let programs: Vec<_> = message.static_account_keys()[13..16]
+            .iter()
+            .map(|key| solana_pubkey_carbon::Pubkey::new_from_array(key.to_bytes()))
+            .collect();
+        let extract = || {
+            NestedInstructions::from(extract_instructions_with_metadata(
+                &transaction_metadata,
+                black_box(&message),
+                black_box(&meta),
+            ))
+        };
+        let single_pass = || {
+            let mut matches = [0usize; 3];
+            walk(&extract(), &mut |instruction| {
+                for (program, count) in programs.iter().zip(&mut matches) {
+                    *count += usize::from(instruction.program_id == *program);
+                }
+            });
+            matches
+        };
+        let per_detector = || {
+            let mut matches = [0usize; 3];
+            for (program, count) in programs.iter().zip(&mut matches) {
+                walk(&extract(), &mut |instruction| {
+                    *count += usize::from(instruction.program_id == *program);
+                });
+            }
+            matches
+        };
+        assert_eq!(single_pass(), per_detector());
+        assert_eq!(single_pass(), [top_level * cpis / 4; 3]);
+
+        println!(
+            "{} top-level instructions, {} inner instructions each, 3 detectors",
+            top_level, cpis
+        );
+        let baseline = common::bench("extraction per detector", per_detector);
+        let candidate = common::bench("single shared extraction", single_pass);
+        common::speedup(baseline, candidate);
+    }
+}
@@ -0,0 +1,92 @@
+//! Extracting and nesting every instruction of transactions with large CPI
+//! trees against extracting the top-level instructions only, as
+//! `--top-level-only` does.
+
+mod common;
+
+use pulstream_plugin::utils::{
+    instruction::NestedInstructions,
+    transformers::{
+        extract_instructions_with_metadata, extract_top_level_instructions_with_metadata,
+    },
+};
+use solana_hash::Hash;
+use solana_message::{
+    compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader, VersionedMessage,
+};
+use solana_pubkey::Pubkey;
+use solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta};
+use std::{hint::black_box, sync::Arc};
+
+fn compiled(program_id_index: u8) -> CompiledInstruction {
+    CompiledInstruction {
+        program_id_index,
+        accounts: (0..12).collect(),
+        data: vec![7; 40],
+    }
+}
+
+/// A transaction of `top_level` instructions, each invoking `cpis` inner
+/// instructions that cycle through stack heights 2 to 5, like routers
+/// swapping through several programs.
+fn transaction(top_level: usize, cpis: usize) -> (VersionedMessage, TransactionStatusMeta) {
+    let message = VersionedMessage::Legacy(Message {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 4,
+        },
+        account_keys: (0..16).map(|_| Pubkey::new_unique()).collect(),
+        recent_blockhash: Hash::default(),
+        instructions: (0..top_level).map(|_| compiled(12)).collect(),
+    });
+    let inner = (0..top_level)
+        .map(|index| InnerInstructions {
+            index: index as u8,
+            instructions: (0..cpis)
+                .map(|cpi| InnerInstruction {
+                    instruction: compiled(12 + (cpi % 4) as u8),
+                    stack_height: Some(2 + (cpi % 4) as u32),
+                })
+                .collect(),
+        })
+        .collect();
+    let meta = TransactionStatusMeta {
+        inner_instructions: Some(inner),
+        ..TransactionStatusMeta::default()
+    };
+    (message, meta)
+}
+
+fn main() {
+    let transaction_metadata = Arc::default();
+    for (top_level, cpis) in [(4, 16), (8, 64), (16, 200)] {
+        let (message, meta) = transaction(top_level, cpis);
+        let full = || {
+            NestedInstructions::from(extract_instructions_with_metadata(
+                &transaction_metadata,
+                black_box(&message),
+                black_box(&meta),
+            ))
+            .total_count()
+        };
+        let top_level_only = || {
+            NestedInstructions::from(extract_top_level_instructions_with_metadata(
+                &transaction_metadata,
+                black_box(&message),
+                black_box(&meta),
+            ))
+            .total_count()
+        };
+        assert_eq!(full(), top_level * (cpis + 1));
+        assert_eq!(top_level_only(), top_level);
+
+        println!(
+            "{} top-level instructions, {} inner instructions each",
+            top_level, cpis
+        );
+        let baseline = common::bench("full extraction and nesting", full);
+        let candidate = common::bench("top-level only", top_level_only);
+        common::speedup(baseline, candidate);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::utils::bloom::KeySet;
+use jetstreamer::firehose::firehose::TransactionData;
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+
+/// Check run on a transaction before any decoding.
+///
+/// The plugin applies its filters in the order they were added and stops at
+/// the first that rejects a transaction, so cheap or selective filters should
+/// come first. Closures of the form `Fn(&TransactionData) -> bool` are
+/// filters too.
+pub trait TransactionFilter: Send + Sync {
+    /// Returns whether `transaction` should be decoded.
+    fn matches(&self, transaction: &TransactionData) -> bool;
+}
+
+impl<F> TransactionFilter for F
+where
+    F: Fn(&TransactionData) -> bool + Send + Sync,
+{
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        self(transaction)
+    }
+}
+
+/// Passes transactions that reference the mint among their account keys,
+/// including addresses loaded from lookup tables, through which routers often
+/// pass the mint.
+#[derive(Debug, Clone, Copy)]
+pub struct MintPresent(pub Pubkey);
+
+impl TransactionFilter for MintPresent {
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        let loaded = &transaction.transaction_status_meta.loaded_addresses;
+        transaction
+            .transaction
+            .message
+            .static_account_keys()
+            .iter()
+            .chain(&loaded.writable)
+            .chain(&loaded.readonly)
+            .any(|key| *key == self.0)
+    }
+}
+
+/// Passes transactions that can invoke the program. Invoked programs, including
+/// those called through CPI, must be static account keys, so addresses loaded
+/// from lookup tables are not checked.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramPresent(pub Pubkey);
+
+impl TransactionFilter for ProgramPresent {
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        transaction
+            .transaction
+            .message
+            .static_account_keys()
+            .contains(&self.0)
+    }
+}
+
+/// Passes transactions that executed successfully.
+#[derive(Debug, Clone, Copy)]
+pub struct SuccessOnly;
+
+impl TransactionFilter for SuccessOnly {
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        transaction.transaction_status_meta.status.is_ok()
+    }
+}
+
+/// Passes transactions whose fee payer (the first account key) is in the set.
+#[derive(Debug, Clone)]
+pub struct FeePayerIn(KeySet);
+
+impl FeePayerIn {
+    /// Builds the filter, prefiltering large sets with a Bloom filter (see
+    /// [`crate::utils::bloom`]).
+    pub fn new(fee_payers: HashSet<Pubkey>) -> Self {
+        Self(KeySet::new(fee_payers))
+    }
+}
+
+impl TransactionFilter for FeePayerIn {
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        transaction
+            .transaction
+            .message
+            .static_account_keys()
+            .first()
+            .is_some_and(|fee_payer| self.0.contains(fee_payer))
+    }
+}
+
+/// Rejects transactions that reference any of the programs in their account
+/// keys, including addresses loaded from lookup tables.
+#[derive(Debug, Clone)]
+pub struct ExcludePrograms(KeySet);
+
+impl ExcludePrograms {
+    /// Builds the filter, prefiltering large sets with a Bloom filter (see
+    /// [`crate::utils::bloom`]).
+    pub fn new(programs: HashSet<Pubkey>) -> Self {
+        Self(KeySet::new(programs))
+    }
+}
+
+impl TransactionFilter for ExcludePrograms {
+    fn matches(&self, transaction: &TransactionData) -> bool {
+        let loaded = &transaction.transaction_status_meta.loaded_addresses;
+        !transaction
+            .transaction
+            .message
+            .static_account_keys()
+            .iter()
+            .chain(&loaded.writable)
+            .chain(&loaded.readonly)
+            .any(|key| self.0.contains(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_hash::Hash;
+    use solana_message::{v0, MessageHeader, VersionedMessage};
+    use solana_signature::Signature;
+    use solana_transaction::versioned::VersionedTransaction;
+    use solana_transaction_status::TransactionStatusMeta;
+
+    /// A v0 transaction with `static_keys` in its message (the first being the
+    /// fee payer) and `loaded` resolved from a lookup table.
+    fn transaction(static_keys: &[Pubkey], loaded: &[Pubkey]) -> TransactionData {
+        let mut meta = TransactionStatusMeta::default();
+        meta.loaded_addresses.writable = loaded.to_vec();
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                ..MessageHeader::default()
+            },
+            account_keys: static_keys.to_vec(),
+            ..v0::Message::default()
+        };
+        TransactionData {
+            slot: 0,
+            transaction_slot_index: 0,
+            signature: Signature::default(),
+            message_hash: Hash::default(),
+            is_vote: false,
+            transaction_status_meta: meta,
+            transaction: VersionedTransaction {
+                signatures: vec![Signature::default()],
+                message: VersionedMessage::V0(message),
+            },
+        }
+    }
+
+    #[test]
+    fn mint_present_checks_loaded_addresses() {
+        let (payer, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let filter = MintPresent(mint);
+        assert!(filter.matches(&transaction(&[payer, mint], &[])));
+        assert!(filter.matches(&transaction(&[payer], &[mint])));
+        assert!(!filter.matches(&transaction(&[payer], &[Pubkey::new_unique()])));
+    }
+
+    #[test]
+    fn exclude_programs_rejects_static_and_loaded_programs() {
+        let (payer, mint, spam) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let filter = ExcludePrograms::new(HashSet::from([spam]));
+        assert!(filter.matches(&transaction(&[payer, mint], &[])));
+        assert!(!filter.matches(&transaction(&[payer, mint, spam], &[])));
+        assert!(!filter.matches(&transaction(&[payer, mint], &[spam])));
+    }
+
+    #[test]
+    fn fee_payer_in_checks_only_the_fee_payer() {
+        let (payer, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // One set below and one above the Bloom filter threshold.
+        for extra in [0, crate::utils::bloom::BLOOM_THRESHOLD] {
+            let mut fee_payers: HashSet<Pubkey> =
+                (0..extra).map(|_| Pubkey::new_unique()).collect();
+            fee_payers.insert(payer);
+            let filter = FeePayerIn::new(fee_payers);
+            assert!(filter.matches(&transaction(&[payer, other], &[])));
+            assert!(!filter.matches(&transaction(&[other, payer], &[])));
+            assert!(!filter.matches(&transaction(&[other], &[payer])));
+            assert!(!filter.matches(&transaction(&[], &[])));
+        }
+    }
+}
@@ -1,5 +1,11 @@
+/// Pre-decode transaction filters.
+pub mod filters;
+
 /// Built-in plugin implementations that ship with Jetstreamer.
 pub mod plugins;
 
+/// Output sinks for decoded trade events.
+pub mod sinks;
+
 /// Utility functions for transforming transaction data.
 pub mod utils;
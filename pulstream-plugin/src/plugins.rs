@@ -1,2 +1,4 @@
 /// Plugin that tracks transactions related to a specific mint address.
 pub mod pumpfun_tracking;
+/// Decorator that records per-hook latency of another plugin.
+pub mod timed;
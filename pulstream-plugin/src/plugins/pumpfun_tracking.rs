@@ -1,23 +1,76 @@
+use crate::filters::{ExcludePrograms, FeePayerIn, MintPresent, ProgramPresent, TransactionFilter};
+use crate::sinks::{
+    channel::{ChannelSink, TradeEventStream},
+    TradeSink,
+};
+#[cfg(feature = "archive")]
+use crate::utils::raw_archive::RawArchive;
+#[cfg(feature = "metadata")]
+use crate::utils::token_metadata::{fetch_token_metadata, TokenMetadata};
 use crate::utils::{
+    balances::{gained_token_account, owner_token_delta, token_account_balance},
+    breadth::{BreadthSnapshot, BreadthWindows},
+    bundle::TransactionBundle,
+    compute_budget::compute_unit_price,
+    curve::curve_progress,
+    dedup::DedupStore,
+    error_report::ErrorReport,
+    ewma::TimeDecayedAverage,
+    hyperloglog::HyperLogLog,
     instruction::{
         InstructionMetadata, InstructionsWithMetadata, NestedInstruction, NestedInstructions,
         TransactionMetadata,
     },
-    transformers::extract_instructions_with_metadata,
+    interarrival::InterArrivalHistograms,
+    latency::LatencyHistogram,
+    leaderboard::MintVolumes,
+    panic_context,
+    pseudonym::AddressHasher,
+    sandwich::{detect_sandwiches, SandwichRole, SandwichTrade},
+    self_test::{fixtures, FIXTURE_MINT},
+    transformers::{
+        extract_instructions_with_metadata, extract_top_level_instructions_with_metadata,
+        has_missing_stack_heights,
+    },
+    vwap::{VwapSnapshot, VwapWindows},
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use carbon_core::{
+    deserialize::CarbonDeserialize,
+    instruction::{DecodedInstruction, InstructionDecoder, MAX_INSTRUCTION_STACK_DEPTH},
 };
-use carbon_core::instruction::InstructionDecoder;
 use clickhouse::Client;
 use futures_util::future::FutureExt;
 use jetstreamer::{
     firehose::firehose::{BlockData, TransactionData},
     plugin::{Plugin, PluginFuture},
 };
-use log::info;
-use solana_message::VersionedMessage;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use solana_instruction::AccountMeta;
 use solana_pubkey::Pubkey;
-use std::sync::Arc;
+use solana_pubkey_carbon::{pubkey, Pubkey as PubkeyCarbon};
+use solana_signature::Signature;
+use solana_transaction_status::{TransactionStatusMeta, TransactionTokenBalance};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use {
-    carbon_pumpfun_decoder::instructions::PumpfunInstruction,
+    carbon_pumpfun_decoder::instructions::{
+        complete_pump_amm_migration_event::CompletePumpAmmMigrationEvent,
+        create_event::CreateEvent, trade_event::TradeEvent as TradeEventData, PumpfunInstruction,
+    },
     carbon_pumpfun_decoder::PumpfunDecoder,
 };
 
@@ -26,52 +79,2847 @@ pub struct TradeEvent<'a> {
     pub metadata: &'a InstructionMetadata,
     pub signature: String,
     pub slot: u64,
+    /// Dotted instruction path within the transaction, see
+    /// [`InstructionMetadata::instruction_path`].
+    pub instruction_path: String,
+    pub timestamp: i64,
+    pub program_id: String,
+    /// Program IDs of the instructions from the top-level one down to the one
+    /// that made the trade, e.g. `[jupiter, pumpfun]` for a trade routed
+    /// through Jupiter, or just `[pumpfun]` for a direct one.
+    pub program_stack: Vec<String>,
+    pub mint: String,
+    pub payer: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub is_buy: bool,
+    /// Compute unit price set by the transaction, in micro-lamports.
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// Accounts of the instruction that emitted the trade, in order. Empty
+    /// unless enabled with [`PumpfunTrackingPlugin::with_include_accounts`].
+    pub accounts: Vec<AccountRef>,
+    /// Token symbol from its metadata account, with
+    /// `PumpfunTrackingPlugin::with_enrich_metadata` (`metadata` feature).
+    pub symbol: Option<String>,
+    /// Time-decayed average price of the mint in SOL per token including this
+    /// trade, with [`PumpfunTrackingPlugin::with_price_half_life`].
+    pub ewma_price: Option<f64>,
+    /// How far the mint's bonding curve is towards graduation after this
+    /// trade, from 0.0 to 1.0, or `None` if the event carried no reserves.
+    /// See [`crate::utils::curve`].
+    pub curve_progress: Option<f64>,
+    /// Whether `is_buy` disagrees with the direction the mint's virtual SOL
+    /// reserves moved since its previous trade, with
+    /// [`PumpfunTrackingPlugin::with_check_direction`]. `None` when unchecked.
+    pub direction_mismatch: Option<bool>,
+    /// Base64 of the data the transaction's last instruction returned through
+    /// `set_return_data`, if any.
+    pub return_data: Option<String>,
+}
+
+/// An account referenced by an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountRef {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&AccountMeta> for AccountRef {
+    fn from(meta: &AccountMeta) -> Self {
+        Self {
+            pubkey: meta.pubkey.to_string(),
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        }
+    }
+}
+
+/// Version of the serialized trade schema, written with every
+/// [`OwnedTradeEvent`] and in the [`ControlEvent::RunStarted`] marker. Bumped
+/// whenever a field is added, removed or changes meaning.
+///
+/// Changelog:
+///
+/// - `1`: first versioned schema. Adds `schema_version` itself to the fields
+///   of `OwnedTradeEvent` as of `program_stack`, `direction_mismatch`,
+///   `program_timestamp` and `ingest_time`. Output written before it has no
+///   version (`0` in protobuf).
+/// - `2`: appends `sandwich_role`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Owned copy of a [`TradeEvent`], detached from the instruction metadata so it
+/// can be buffered or handed to a sink.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedTradeEvent {
+    /// [`SCHEMA_VERSION`] of the code that built the event. Kept first so it
+    /// leads every bincode record.
+    pub schema_version: u32,
+    pub signature: String,
+    pub slot: u64,
+    pub instruction_path: String,
+    /// Unix timestamp from the source selected with
+    /// [`PumpfunTrackingPlugin::with_timestamp_source`], the program's by
+    /// default.
     pub timestamp: i64,
+    /// Unix timestamp the pumpfun program logged in its trade event.
+    pub program_timestamp: i64,
+    /// Unix timestamp at which the plugin processed the transaction.
+    pub ingest_time: Option<i64>,
     pub program_id: String,
+    pub program_stack: Vec<String>,
     pub mint: String,
     pub payer: String,
     pub amount_in: u64,
     pub amount_out: u64,
     pub is_buy: bool,
+    pub priority_fee_micro_lamports: Option<u64>,
+    pub accounts: Vec<AccountRef>,
+    pub symbol: Option<String>,
+    pub ewma_price: Option<f64>,
+    pub curve_progress: Option<f64>,
+    pub direction_mismatch: Option<bool>,
+    /// Unix timestamp of the block containing the trade, with
+    /// [`PumpfunTrackingPlugin::with_block_time`].
+    pub block_time: Option<i64>,
+    pub return_data: Option<String>,
+    /// Milliseconds from `block_time` to when the plugin processed the trade,
+    /// with [`PumpfunTrackingPlugin::with_block_time`]. Only meaningful for
+    /// live slots; for historical ones it is the age of the block.
+    pub processing_latency_ms: Option<i64>,
+    /// `frontrun`, `victim` or `backrun` if the trade is part of a sandwich,
+    /// with [`PumpfunTrackingPlugin::with_detect_sandwiches`]. See
+    /// [`crate::utils::sandwich`].
+    pub sandwich_role: Option<String>,
+}
+
+impl From<&TradeEvent<'_>> for OwnedTradeEvent {
+    fn from(event: &TradeEvent<'_>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            signature: event.signature.clone(),
+            slot: event.slot,
+            instruction_path: event.instruction_path.clone(),
+            timestamp: event.timestamp,
+            program_timestamp: event.timestamp,
+            ingest_time: None,
+            program_id: event.program_id.clone(),
+            program_stack: event.program_stack.clone(),
+            mint: event.mint.clone(),
+            payer: event.payer.clone(),
+            amount_in: event.amount_in,
+            amount_out: event.amount_out,
+            is_buy: event.is_buy,
+            priority_fee_micro_lamports: event.priority_fee_micro_lamports,
+            accounts: event.accounts.clone(),
+            symbol: event.symbol.clone(),
+            ewma_price: event.ewma_price,
+            curve_progress: event.curve_progress,
+            direction_mismatch: event.direction_mismatch,
+            block_time: event.metadata.transaction_metadata.block_time,
+            return_data: event.return_data.clone(),
+            processing_latency_ms: None,
+            sandwich_role: None,
+        }
+    }
+}
+
+/// A token graduating from its bonding curve and migrating its liquidity to an
+/// AMM pool.
+///
+/// Decoded from pumpfun's `CompletePumpAmmMigrationEvent`. The older
+/// Raydium migration path is not exposed by the decoder, so graduations from
+/// before the switch to the pump AMM are not reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction_path: String,
+    pub timestamp: i64,
+    pub mint: String,
+    /// AMM pool the liquidity was migrated to.
+    pub pool: String,
+    pub bonding_curve: String,
+    /// Account that triggered the migration.
+    pub user: String,
+    pub mint_amount: u64,
+    pub sol_amount: u64,
+    pub pool_migration_fee: u64,
+}
+
+/// Marker bracketing a run in the event stream, so a downstream consumer
+/// knows when all of a run's events have been written.
+///
+/// `start_slot` and `end_slot` (exclusive) are the run's slot range as set
+/// with [`PumpfunTrackingPlugin::with_slot_range`], if any. Serialized with a
+/// `type` tag, e.g. `{"type":"run_started","mint":..,..}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlEvent {
+    /// Written in `on_load`, before any other event of the run.
+    RunStarted {
+        /// [`SCHEMA_VERSION`] of the events that follow.
+        schema_version: u32,
+        mint: String,
+        start_slot: Option<u64>,
+        end_slot: Option<u64>,
+    },
+    /// Written in `on_exit`, after every other event of the run.
+    RunFinished {
+        mint: String,
+        start_slot: Option<u64>,
+        end_slot: Option<u64>,
+        /// Highest slot processed.
+        last_slot: u64,
+        /// Trade events matched over the plugin's lifetime.
+        events: u64,
+    },
+}
+
+/// Trades of the tracked mint in one block, emitted with
+/// [`PumpfunTrackingPlugin::with_block_summary`] instead of the trades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockSummary {
+    pub slot: u64,
+    /// Unix timestamp of the block, if the firehose reported one.
+    pub block_time: Option<i64>,
+    pub mint: String,
+    pub buys: u64,
+    pub sells: u64,
+    /// Lamports traded in either direction.
+    pub sol_volume: u128,
+    /// Raw token units traded in either direction.
+    pub token_volume: u128,
+    /// Lamports spent on buys minus lamports received from sells.
+    pub net_sol: i128,
+    /// Raw token units bought minus raw token units sold.
+    pub net_tokens: i128,
+}
+
+impl BlockSummary {
+    fn add(&mut self, event: &TradeEvent) {
+        // Buys pay SOL for tokens; sells pay tokens for SOL.
+        let (sol, tokens) = if event.is_buy {
+            self.buys += 1;
+            (event.amount_in, event.amount_out)
+        } else {
+            self.sells += 1;
+            (event.amount_out, event.amount_in)
+        };
+        let sign = if event.is_buy { 1 } else { -1 };
+        self.sol_volume += u128::from(sol);
+        self.token_volume += u128::from(tokens);
+        self.net_sol += sign * i128::from(sol);
+        self.net_tokens += sign * i128::from(tokens);
+    }
+}
+
+/// Net change in one payer's position in the tracked mint over a run, written
+/// by [`PumpfunTrackingPlugin::write_net_positions`] with
+/// [`PumpfunTrackingPlugin::with_net_positions`] instead of the trades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetPosition {
+    pub mint: String,
+    pub payer: String,
+    pub buys: u64,
+    pub sells: u64,
+    /// Lamports spent on buys minus lamports received from sells.
+    pub net_sol: i128,
+    /// Raw token units bought minus raw token units sold.
+    pub net_tokens: i128,
+}
+
+impl NetPosition {
+    fn add(&mut self, event: &TradeEvent) {
+        let (sol, tokens) = if event.is_buy {
+            self.buys += 1;
+            (event.amount_in, event.amount_out)
+        } else {
+            self.sells += 1;
+            (event.amount_out, event.amount_in)
+        };
+        let sign = if event.is_buy { 1 } else { -1 };
+        self.net_sol += sign * i128::from(sol);
+        self.net_tokens += sign * i128::from(tokens);
+    }
+}
+
+/// Net positions accumulated so far, by payer.
+#[derive(Debug, Default)]
+struct NetPositions {
+    /// Most payers tracked; trades of further payers are only counted.
+    max_payers: Option<usize>,
+    payers: HashMap<String, NetPosition>,
+    untracked: u64,
+}
+
+impl NetPositions {
+    fn add(&mut self, event: &TradeEvent) {
+        if let Some(position) = self.payers.get_mut(&event.payer) {
+            position.add(event);
+            return;
+        }
+        if self.max_payers.is_some_and(|max| self.payers.len() >= max) {
+            self.untracked += 1;
+            return;
+        }
+        let mut position = NetPosition {
+            mint: event.mint.clone(),
+            payer: event.payer.clone(),
+            ..Default::default()
+        };
+        position.add(event);
+        self.payers.insert(event.payer.clone(), position);
+    }
+}
+
+/// Creation of the first token account of the tracked mint for an owner,
+/// emitted with [`PumpfunTrackingPlugin::with_track_holders`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewHolderEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction_path: String,
+    /// Wallet that now holds a token account of the mint.
+    pub owner: String,
+    pub mint: String,
+    /// Associated token account that was created.
+    pub token_account: String,
+}
+
+/// SPL token transfer of the tracked mint within a matched transaction,
+/// emitted with [`PumpfunTrackingPlugin::with_track_transfers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction_path: String,
+    pub mint: String,
+    /// Token account the tokens left.
+    pub source: String,
+    /// Token account the tokens arrived in.
+    pub destination: String,
+    /// Owners of the two token accounts, from the transaction's token
+    /// balances; `None` for blocks recorded before balances were tracked.
+    pub source_owner: Option<String>,
+    pub destination_owner: Option<String>,
+    /// Owner or delegate that signed the transfer.
+    pub authority: String,
+    /// Amount in raw token units.
+    pub amount: u64,
+}
+
+/// Raw instruction of a watched program that the decoder did not recognise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndecodedInstruction {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction_path: String,
+    pub stack_height: u32,
+    pub program_id: String,
+    /// Instruction data, hex encoded.
+    pub data: String,
+    /// Account keys in instruction order.
+    pub accounts: Vec<String>,
 }
 
 pub type TradeEventProcessor = std::sync::Arc<dyn Fn(&TradeEvent) + Send + Sync + 'static>;
 
-#[derive(Clone)]
-/// Simple plugin that checks if transactions contain a specific mint address.
-pub struct PumpfunTrackingPlugin {
-    /// The mint address to check for
-    pub mint: Pubkey,
-    /// Callback to process decoded trade events
-    pub processor: TradeEventProcessor,
-}
+/// Closure applied to each owned event between decoding and sink dispatch.
+pub type TradeEventTransform = Arc<dyn Fn(&mut OwnedTradeEvent) + Send + Sync + 'static>;
+
+/// Decoder the plugin runs on each instruction. [`PumpfunDecoder`] by default;
+/// see [`PumpfunTrackingPlugin::with_decoder`].
+pub type PumpfunInstructionDecoder =
+    dyn for<'a> InstructionDecoder<'a, InstructionType = PumpfunInstruction> + Send + Sync;
+
+/// A transaction that was skipped because decoding it failed or panicked.
+#[derive(Debug, Clone)]
+pub struct SkippedTransaction {
+    pub slot: u64,
+    pub signature: String,
+    pub kind: SkipKind,
+    pub reason: String,
+}
+
+/// Why a transaction was skipped under
+/// [`PumpfunTrackingPlugin::with_skip_errors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipKind {
+    /// See [`ProcessError::DecodeFailed`].
+    DecodeFailed,
+    /// See [`ProcessError::SinkFailed`].
+    SinkFailed,
+    /// See [`ProcessError::MetadataInvalid`].
+    MetadataInvalid,
+    /// Processing panicked outside the decoder.
+    Panicked,
+}
+
+impl SkipKind {
+    /// Every kind, in the order they are reported.
+    pub const ALL: [Self; 4] = [
+        Self::DecodeFailed,
+        Self::SinkFailed,
+        Self::MetadataInvalid,
+        Self::Panicked,
+    ];
+
+    /// Plural name of the kind, as used in the exit report.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DecodeFailed => "decode failures",
+            Self::SinkFailed => "sink failures",
+            Self::MetadataInvalid => "invalid metadata",
+            Self::Panicked => "panics",
+        }
+    }
+}
+
+impl From<&ProcessError> for SkipKind {
+    fn from(err: &ProcessError) -> Self {
+        match err {
+            ProcessError::DecodeFailed { .. } => Self::DecodeFailed,
+            ProcessError::SinkFailed { .. } => Self::SinkFailed,
+            ProcessError::MetadataInvalid { .. } => Self::MetadataInvalid,
+        }
+    }
+}
+
+/// A failure while processing a transaction. Returned from the plugin hooks,
+/// which aborts the run, unless [`PumpfunTrackingPlugin::with_skip_errors`]
+/// is set.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProcessError {
+    /// An instruction with the discriminator of an event the plugin emits did
+    /// not decode, for example a trade event in a layout the decoder does not
+    /// know. Decoder panics are not errors, see [`PumpfunTrackingPlugin`].
+    #[error("failed to decode instruction {instruction_path} of {signature}")]
+    DecodeFailed {
+        signature: String,
+        instruction_path: String,
+    },
+    /// A sink failed to write an event.
+    #[error("failed to write {event} of {signature}: {reason}")]
+    SinkFailed {
+        signature: String,
+        event: &'static str,
+        reason: String,
+    },
+    /// The transaction's status metadata does not fit its message.
+    #[error("invalid metadata in {signature}: {reason}")]
+    MetadataInvalid { signature: String, reason: String },
+}
+
+/// What to do with a slot the firehose delivers more than once, for example
+/// after a reconnect or with overlapping ranges.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateSlotPolicy {
+    /// Process it again, without tracking slots.
+    #[default]
+    Process,
+    /// Process it again and log a warning.
+    Warn,
+    /// Ignore its transactions and block.
+    Skip,
+}
+
+impl std::str::FromStr for DuplicateSlotPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "process" => Ok(Self::Process),
+            "warn" => Ok(Self::Warn),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!(
+                "unknown duplicate slot policy: {other} (expected skip, warn or process)"
+            )),
+        }
+    }
+}
+
+/// Which time populates [`OwnedTradeEvent::timestamp`] for the sinks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampSource {
+    /// The `timestamp` the pumpfun program logged in its trade event, the
+    /// validator clock while executing the transaction.
+    #[default]
+    Program,
+    /// The block time recorded by the cluster. Needs
+    /// [`PumpfunTrackingPlugin::with_block_time`].
+    Block,
+    /// When the plugin processed the transaction, by the local wall clock.
+    Ingest,
+}
+
+impl std::str::FromStr for TimestampSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "program" => Ok(Self::Program),
+            "block" => Ok(Self::Block),
+            "ingest" => Ok(Self::Ingest),
+            other => Err(format!(
+                "unknown timestamp source: {other} (expected program, block or ingest)"
+            )),
+        }
+    }
+}
+
+/// Checks that the inner instructions of `transaction` can be nested under
+/// its top-level instructions.
+fn check_inner_instructions(transaction: &TransactionData) -> Result<(), ProcessError> {
+    let Some(inner) = &transaction.transaction_status_meta.inner_instructions else {
+        return Ok(());
+    };
+    let invalid = |reason: String| ProcessError::MetadataInvalid {
+        signature: transaction.signature.to_string(),
+        reason,
+    };
+    let top_level = transaction.transaction.message.instructions().len();
+    for inner_instructions in inner {
+        if usize::from(inner_instructions.index) >= top_level {
+            return Err(invalid(format!(
+                "inner instructions reference instruction {} of {}",
+                inner_instructions.index, top_level
+            )));
+        }
+        for instruction in &inner_instructions.instructions {
+            if let Some(height) = instruction.stack_height {
+                if height == 0 || height as usize > MAX_INSTRUCTION_STACK_DEPTH {
+                    return Err(invalid(format!(
+                        "inner instruction of {} has stack height {}",
+                        inner_instructions.index, height
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the program IDs from the top-level instruction down to the one that
+/// emitted the event logged by `event`, for [`TradeEvent::program_stack`].
+/// Pumpfun logs events through a self-CPI, so its parent is the emitting
+/// instruction; an event without ancestors is its own emitter.
+fn program_stack(ancestors: &[&NestedInstruction], event: &NestedInstruction) -> Vec<String> {
+    if ancestors.is_empty() {
+        return vec![event.instruction.program_id.to_string()];
+    }
+    ancestors
+        .iter()
+        .map(|instruction| instruction.instruction.program_id.to_string())
+        .collect()
+}
+
+/// Returns the transaction's return data as base64, or `None` if it set none.
+fn return_data(meta: &TransactionStatusMeta) -> Option<String> {
+    meta.return_data
+        .as_ref()
+        .filter(|return_data| !return_data.data.is_empty())
+        .map(|return_data| STANDARD.encode(&return_data.data))
+}
+
+/// Returns the wall-clock time in Unix milliseconds.
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// Extracts the message from a caught panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic with non-string payload".to_string()
+    }
+}
+
+/// Progress counters shared by every clone of the plugin.
+#[derive(Debug, Default)]
+struct TrackingStats {
+    /// Highest slot whose block has been observed.
+    last_slot: AtomicU64,
+    /// Number of trade events emitted so far.
+    matches: AtomicU64,
+    /// Whether any transaction involving the mint was seen.
+    mint_seen: AtomicBool,
+    /// Instructions on which the decoder panicked.
+    decoder_panics: AtomicU64,
+    /// Instructions not decoded because their data exceeded the size limit.
+    oversized: AtomicU64,
+    /// Events not emitted because the dedup store already had them.
+    deduplicated: AtomicU64,
+    /// Events not emitted because their slot was in the warmup.
+    warmup_suppressed: AtomicU64,
+    /// Transactions whose inner instructions lacked stack heights.
+    inferred_stack_heights: AtomicU64,
+    /// Transactions dropped while processing was paused.
+    paused_transactions: AtomicU64,
+}
+
+/// Liveness log emitted while no trade events are matching.
+#[derive(Debug)]
+struct Heartbeat {
+    interval: Duration,
+    /// Start of the current interval and the match count at that point.
+    window: Mutex<(Instant, u64)>,
+}
+
+impl Heartbeat {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Logs a heartbeat if a full interval passed without any new matches.
+    fn tick(&self, stats: &TrackingStats) {
+        // Another thread is already checking; skipping this block is fine.
+        let Ok(mut window) = self.window.try_lock() else {
+            return;
+        };
+        if window.0.elapsed() < self.interval {
+            return;
+        }
+        let matches = stats.matches.load(Ordering::Relaxed);
+        if matches == window.1 {
+            info!(
+                "processed up to slot {}, {} matches so far",
+                stats.last_slot.load(Ordering::Relaxed),
+                matches
+            );
+        }
+        *window = (Instant::now(), matches);
+    }
+}
+
+/// Machine-readable progress file, rewritten at most once per interval.
+#[derive(Debug)]
+struct StatusFile {
+    path: PathBuf,
+    interval: Duration,
+    started: Instant,
+    /// When the file was last written, `None` before the first write.
+    last_write: Mutex<Option<Instant>>,
+}
+
+impl StatusFile {
+    /// Rewrites the file if a full interval passed since the last write.
+    fn tick(&self, stats: &TrackingStats) {
+        // Another thread is already writing; skipping this block is fine.
+        let Ok(mut last_write) = self.last_write.try_lock() else {
+            return;
+        };
+        if last_write.is_some_and(|at| at.elapsed() < self.interval) {
+            return;
+        }
+        self.write(stats);
+        *last_write = Some(Instant::now());
+    }
+
+    /// Replaces the file through a temporary file and a rename, so readers
+    /// never see a partial write.
+    fn write(&self, stats: &TrackingStats) {
+        let updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let status = format!(
+            "{{\"last_slot\":{},\"events\":{},\"uptime_secs\":{},\"updated_at\":{}}}\n",
+            stats.last_slot.load(Ordering::Relaxed),
+            stats.matches.load(Ordering::Relaxed),
+            self.started.elapsed().as_secs(),
+            updated_at
+        );
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let result = std::fs::write(&tmp, status).and_then(|()| std::fs::rename(&tmp, &self.path));
+        if let Err(err) = result {
+            warn!(
+                "Failed to write status file {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Cross-checks decoded token amounts against the transaction's token balance
+/// deltas.
+#[derive(Debug, Default)]
+struct BalanceVerifier {
+    /// Allowed difference in raw token units.
+    tolerance: u64,
+    checked: AtomicU64,
+    mismatches: AtomicU64,
+    /// Trades skipped because the transaction carries no token balances.
+    unavailable: AtomicU64,
+}
+
+impl BalanceVerifier {
+    fn check(&self, meta: &TransactionStatusMeta, trade: &TradeEventData, signature: &str) {
+        let mint = trade.mint.to_string();
+        let user = trade.user.to_string();
+        let Some(delta) = owner_token_delta(meta, &mint, &user) else {
+            self.unavailable.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        self.checked.fetch_add(1, Ordering::Relaxed);
+        let expected = if trade.is_buy {
+            trade.token_amount as i128
+        } else {
+            -(trade.token_amount as i128)
+        };
+        let diff = delta.abs_diff(expected);
+        if diff > self.tolerance as u128 {
+            self.mismatches.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Balance mismatch in {}: decoded {} {} tokens for {}, balance delta is {} (off by {})",
+                signature,
+                if trade.is_buy { "buy of" } else { "sell of" },
+                trade.token_amount,
+                user,
+                delta,
+                diff
+            );
+        }
+    }
+
+    fn report(&self) {
+        let checked = self.checked.load(Ordering::Relaxed);
+        let mismatches = self.mismatches.load(Ordering::Relaxed);
+        let rate = if checked == 0 {
+            0.0
+        } else {
+            mismatches as f64 * 100.0 / checked as f64
+        };
+        info!(
+            "Balance verification: {} of {} trades mismatched ({:.2}%), {} without token balances",
+            mismatches,
+            checked,
+            rate,
+            self.unavailable.load(Ordering::Relaxed)
+        );
+    }
+}
+
+/// Cross-checks the decoded direction of trades against the movement of their
+/// mint's virtual SOL reserves: a buy adds SOL to the curve and a sell removes
+/// it. Trades of a mint are compared in the order they are processed, which
+/// with several threads is not always chain order, so a mismatch is a lead to
+/// inspect rather than proof of a decoding error.
+#[derive(Debug, Default)]
+struct DirectionChecker {
+    /// Virtual SOL reserves after the latest trade of each mint.
+    last_reserves: Mutex<HashMap<String, u64>>,
+    checked: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+impl DirectionChecker {
+    /// Returns whether `trade` moved its mint's reserves against its
+    /// direction, or `None` if there is nothing to compare with: the first
+    /// trade of a mint, a trade without reserves, or unchanged reserves.
+    fn check(&self, trade: &TradeEventData, signature: &str) -> Option<bool> {
+        if trade.virtual_sol_reserves == 0 {
+            return None;
+        }
+        let mint = trade.mint.to_string();
+        let previous = self
+            .last_reserves
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(mint.clone(), trade.virtual_sol_reserves)?;
+        if previous == trade.virtual_sol_reserves {
+            return None;
+        }
+        self.checked.fetch_add(1, Ordering::Relaxed);
+        let mismatch = (trade.virtual_sol_reserves > previous) != trade.is_buy;
+        if mismatch {
+            self.mismatches.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Direction mismatch in {}: decoded a {} of {}, but virtual SOL reserves went from {} to {}",
+                signature,
+                if trade.is_buy { "buy" } else { "sell" },
+                mint,
+                previous,
+                trade.virtual_sol_reserves
+            );
+        }
+        Some(mismatch)
+    }
+
+    fn report(&self) {
+        let checked = self.checked.load(Ordering::Relaxed);
+        let mismatches = self.mismatches.load(Ordering::Relaxed);
+        let rate = if checked == 0 {
+            0.0
+        } else {
+            mismatches as f64 * 100.0 / checked as f64
+        };
+        info!(
+            "Direction check: {} of {} trades disagreed with their reserve movement ({:.2}%)",
+            mismatches, checked, rate
+        );
+    }
+}
+
+/// Distinct trade payers seen during the run.
+#[derive(Debug)]
+enum PayerCounter {
+    Exact(HashSet<String>),
+    /// Bounded-memory estimate for runs with very many payers.
+    Approx(HyperLogLog),
+}
+
+impl Default for PayerCounter {
+    fn default() -> Self {
+        Self::Exact(HashSet::new())
+    }
+}
+
+impl PayerCounter {
+    fn insert(&mut self, payer: &str) {
+        match self {
+            Self::Exact(payers) => {
+                if !payers.contains(payer) {
+                    payers.insert(payer.to_string());
+                }
+            }
+            Self::Approx(sketch) => sketch.insert(payer),
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            Self::Exact(payers) => format!("{} unique payers", payers.len()),
+            Self::Approx(sketch) => {
+                format!("~{:.0} unique payers (approximate)", sketch.estimate())
+            }
+        }
+    }
+}
+
+/// Trade events written by [`PumpfunTrackingPlugin::process_transaction`].
+#[derive(Default)]
+struct CollectSink {
+    events: Mutex<Vec<OwnedTradeEvent>>,
+}
+
+impl TradeSink for CollectSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        self.events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(event.clone());
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Slots whose block has been seen, for spotting slots delivered twice.
+#[derive(Debug)]
+struct SlotTracker {
+    policy: DuplicateSlotPolicy,
+    completed: Mutex<HashSet<u64>>,
+    duplicates: Mutex<HashSet<u64>>,
+}
+
+impl SlotTracker {
+    fn new(policy: DuplicateSlotPolicy) -> Self {
+        Self {
+            policy,
+            completed: Mutex::default(),
+            duplicates: Mutex::default(),
+        }
+    }
+
+    /// Records a transaction of `slot`, returning whether it should be
+    /// skipped. The firehose reports a block after its transactions, so a
+    /// transaction of a completed slot is a repeat.
+    fn on_transaction(&self, slot: u64) -> bool {
+        let repeated = self
+            .completed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(&slot);
+        repeated && self.duplicate(slot)
+    }
+
+    /// Records the block of `slot`, returning whether it should be skipped.
+    fn on_block(&self, slot: u64) -> bool {
+        let first = self
+            .completed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(slot);
+        !first && self.duplicate(slot)
+    }
+
+    fn duplicate(&self, slot: u64) -> bool {
+        let new = self
+            .duplicates
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(slot);
+        if new && self.policy == DuplicateSlotPolicy::Warn {
+            warn!("duplicate slot {}: already processed", slot);
+        }
+        self.policy == DuplicateSlotPolicy::Skip
+    }
+
+    /// Adds the slots delivered more than once to `report`.
+    fn report(&self, report: &mut ErrorReport) {
+        let mut duplicates: Vec<u64> = self
+            .duplicates
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .copied()
+            .collect();
+        duplicates.sort_unstable();
+        let kind = if self.policy == DuplicateSlotPolicy::Skip {
+            "duplicate slots (skipped)"
+        } else {
+            "duplicate slots (processed again)"
+        };
+        report.add(
+            kind,
+            duplicates.len() as u64,
+            duplicates.iter().map(|slot| format!("slot {slot}")),
+        );
+    }
+}
+
+/// Lookups of token metadata that may run at once by default.
+#[cfg(feature = "metadata")]
+pub const DEFAULT_ENRICH_CONCURRENCY: usize = 4;
+
+/// Fetches the metadata of a mint: `getAccountInfo` over RPC, or a stand-in
+/// in tests.
+#[cfg(feature = "metadata")]
+type MetadataLookup = Arc<
+    dyn Fn(
+            PubkeyCarbon,
+        ) -> futures_util::future::BoxFuture<
+            'static,
+            Result<Option<TokenMetadata>, Box<dyn std::error::Error + Send + Sync>>,
+        > + Send
+        + Sync,
+>;
+
+/// Per-mint cache of token metadata looked up over RPC.
+#[cfg(feature = "metadata")]
+struct MetadataEnricher {
+    lookup: MetadataLookup,
+    /// Bounds the lookups in flight.
+    permits: tokio::sync::Semaphore,
+    /// One cell per mint, so concurrent lookups of a mint wait on the first
+    /// instead of repeating it. `None` values record mints without metadata
+    /// so they are not retried.
+    cache: Mutex<HashMap<String, Arc<tokio::sync::OnceCell<Option<TokenMetadata>>>>>,
+}
+
+#[cfg(feature = "metadata")]
+impl MetadataEnricher {
+    fn new(rpc_url: String, concurrency: usize) -> Self {
+        let client = reqwest::Client::new();
+        Self::with_lookup(
+            Arc::new(move |mint| {
+                let (client, rpc_url) = (client.clone(), rpc_url.clone());
+                async move { fetch_token_metadata(&client, &rpc_url, &mint).await }.boxed()
+            }),
+            concurrency,
+        )
+    }
+
+    fn with_lookup(lookup: MetadataLookup, concurrency: usize) -> Self {
+        Self {
+            lookup,
+            permits: tokio::sync::Semaphore::new(concurrency.max(1)),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Looks up `mint` unless it has been seen before, waiting for a lookup
+    /// already in flight.
+    async fn ensure(&self, mint: &Pubkey) {
+        let key = mint.to_string();
+        let cell = self
+            .cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        cell.get_or_init(|| self.lookup(&key, mint)).await;
+    }
+
+    async fn lookup(&self, key: &str, mint: &Pubkey) -> Option<TokenMetadata> {
+        // The semaphore is never closed.
+        let _permit = self.permits.acquire().await.ok()?;
+        match (self.lookup)(PubkeyCarbon::from(mint.to_bytes())).await {
+            Ok(Some(metadata)) => {
+                info!(
+                    "Token metadata for {}: {} ({})",
+                    key, metadata.name, metadata.symbol
+                );
+                Some(metadata)
+            }
+            Ok(None) => {
+                warn!("No token metadata found for {}", key);
+                None
+            }
+            Err(err) => {
+                warn!("Token metadata lookup for {} failed: {}", key, err);
+                None
+            }
+        }
+    }
+
+    /// Returns the finished lookup of `mint`, if any.
+    fn cached(&self, mint: &str) -> Option<Option<TokenMetadata>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(mint)?
+            .get()
+            .cloned()
+    }
+}
+
+/// Default limit on instruction data passed to the decoder, in bytes.
+pub const DEFAULT_MAX_INSTRUCTION_DATA: usize = 10 * 1024;
+
+/// Size of the dedup store entries buffered in memory before recording
+/// another one flushes the sinks and then the store.
+const DEDUP_FLUSH_BYTES: usize = 64 * 1024;
+
+/// Trades awaiting their block time, keyed by slot, each with the wall-clock
+/// time it was processed at in Unix milliseconds.
+type PendingTrades = Mutex<HashMap<u64, Vec<(OwnedTradeEvent, i64)>>>;
+
+/// Block summaries being accumulated, keyed by slot.
+type PendingSummaries = Mutex<HashMap<u64, BlockSummary>>;
+
+/// Address of the pumpfun program, matched instead of the mint in breadth mode.
+const PUMPFUN_PROGRAM_ID: Pubkey =
+    solana_pubkey::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+/// Address of the associated token account program.
+const ASSOCIATED_TOKEN_PROGRAM_ID: PubkeyCarbon =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Addresses of the SPL token and token-2022 programs, which share the
+/// `Transfer` and `TransferChecked` layouts.
+const TOKEN_PROGRAM_ID: PubkeyCarbon = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_2022_PROGRAM_ID: PubkeyCarbon = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+#[derive(Clone)]
+/// Simple plugin that checks if transactions contain a specific mint address.
+pub struct PumpfunTrackingPlugin {
+    /// The mint address to check for
+    pub mint: Pubkey,
+    /// Callback to process decoded trade events
+    pub processor: TradeEventProcessor,
+    stats: Arc<TrackingStats>,
+    paused: Arc<AtomicBool>,
+    heartbeat: Option<Arc<Heartbeat>>,
+    status_file: Option<Arc<StatusFile>>,
+    skip_errors: bool,
+    skipped: Arc<Mutex<Vec<SkippedTransaction>>>,
+    balance_verifier: Option<Arc<BalanceVerifier>>,
+    direction_checker: Option<Arc<DirectionChecker>>,
+    /// Number of sandwiches found, with [`Self::with_detect_sandwiches`].
+    sandwiches: Option<Arc<AtomicU64>>,
+    sinks: Vec<Arc<dyn TradeSink>>,
+    transforms: Vec<TradeEventTransform>,
+    address_hasher: Option<AddressHasher>,
+    decoder: Arc<PumpfunInstructionDecoder>,
+    track_migrations: bool,
+    min_stack_height: Option<u32>,
+    max_stack_height: Option<u32>,
+    min_curve_progress: Option<f64>,
+    max_curve_progress: Option<f64>,
+    /// Whether trades without reserves pass the curve progress range.
+    pass_missing_curve_progress: bool,
+    timestamp_source: TimestampSource,
+    dump_undecoded: Option<Pubkey>,
+    payers: Arc<Mutex<PayerCounter>>,
+    strict_mint: bool,
+    include_accounts: bool,
+    #[cfg(feature = "metadata")]
+    metadata: Option<Arc<MetadataEnricher>>,
+    #[cfg(feature = "metadata")]
+    enrich_concurrency: usize,
+    filters: Vec<Arc<dyn TransactionFilter>>,
+    dedup: Option<Arc<Mutex<DedupStore>>>,
+    track_holders: bool,
+    track_transfers: bool,
+    prices: Option<Arc<Mutex<TimeDecayedAverage>>>,
+    /// Trades held back until their block's time is known, by slot.
+    pending_by_slot: Option<Arc<PendingTrades>>,
+    /// Whether [`Self::with_block_time`] was set; see [`Self::hold_by_slot`].
+    block_time: bool,
+    latencies: Arc<Mutex<LatencyHistogram>>,
+    block_summaries: Option<Arc<PendingSummaries>>,
+    net_positions: Option<Arc<Mutex<NetPositions>>>,
+    slot_tracker: Option<Arc<SlotTracker>>,
+    /// Creator of each mint whose create event was seen, by mint.
+    creators: Option<Arc<Mutex<HashMap<String, String>>>>,
+    max_instruction_data: usize,
+    export: Option<(Signature, PathBuf)>,
+    #[cfg(feature = "archive")]
+    raw_archive: Option<Arc<RawArchive>>,
+    vwap: Option<Arc<Mutex<VwapWindows>>>,
+    top_level_only: bool,
+    breadth: Option<Arc<Mutex<BreadthWindows>>>,
+    /// Whether `mint` was given; see [`Self::with_track_mint`].
+    track_mint: bool,
+    interarrival: Option<Arc<Mutex<InterArrivalHistograms>>>,
+    /// Number of mints to rank at exit, with their volumes.
+    top_mints: Option<(usize, Arc<Mutex<MintVolumes>>)>,
+    slot_range: Option<Range<u64>>,
+    /// Whether this runner starts and ends the run, see
+    /// [`Self::with_run_segment`].
+    first_segment: bool,
+    last_segment: bool,
+    /// Slots whose events are suppressed, with [`Self::with_warmup_slots`].
+    warmup: Range<u64>,
+}
+
+impl PumpfunTrackingPlugin {
+    /// Creates a new PumpfunTrackingPlugin for the specified mint address
+    pub fn new(mint: Pubkey) -> Self {
+        Self::with_processor(mint, std::sync::Arc::new(|_evt: &TradeEvent| {}))
+    }
+
+    /// Creates a new PumpfunTrackingPlugin with a custom event processor
+    pub fn with_processor(mint: Pubkey, processor: TradeEventProcessor) -> Self {
+        Self {
+            mint,
+            processor,
+            stats: Arc::default(),
+            paused: Arc::default(),
+            heartbeat: None,
+            status_file: None,
+            skip_errors: false,
+            skipped: Arc::default(),
+            balance_verifier: None,
+            direction_checker: None,
+            sandwiches: None,
+            sinks: Vec::new(),
+            transforms: Vec::new(),
+            address_hasher: None,
+            decoder: Arc::new(PumpfunDecoder),
+            track_migrations: false,
+            min_stack_height: None,
+            max_stack_height: None,
+            min_curve_progress: None,
+            max_curve_progress: None,
+            pass_missing_curve_progress: false,
+            timestamp_source: TimestampSource::Program,
+            dump_undecoded: None,
+            payers: Arc::default(),
+            strict_mint: false,
+            include_accounts: false,
+            #[cfg(feature = "metadata")]
+            metadata: None,
+            #[cfg(feature = "metadata")]
+            enrich_concurrency: DEFAULT_ENRICH_CONCURRENCY,
+            filters: Vec::new(),
+            dedup: None,
+            track_holders: false,
+            track_transfers: false,
+            prices: None,
+            pending_by_slot: None,
+            block_time: false,
+            latencies: Arc::default(),
+            block_summaries: None,
+            net_positions: None,
+            slot_tracker: None,
+            creators: None,
+            max_instruction_data: DEFAULT_MAX_INSTRUCTION_DATA,
+            export: None,
+            #[cfg(feature = "archive")]
+            raw_archive: None,
+            vwap: None,
+            top_level_only: false,
+            breadth: None,
+            track_mint: true,
+            interarrival: None,
+            top_mints: None,
+            slot_range: None,
+            first_segment: true,
+            last_segment: true,
+            warmup: 0..0,
+        }
+    }
+
+    /// Creates a plugin whose trade events, after transforms, are delivered
+    /// through the returned stream instead of a callback. Up to `capacity`
+    /// events are buffered before the plugin waits for the consumer.
+    ///
+    /// The stream ends once every clone of the plugin has been dropped.
+    pub fn with_stream(mint: Pubkey, capacity: usize) -> (Self, TradeEventStream) {
+        let (sink, stream) = ChannelSink::new(capacity);
+        (Self::new(mint).with_sink(Arc::new(sink)), stream)
+    }
+
+    /// Sets the slot range reported in the run's [`ControlEvent`] markers. It
+    /// does not restrict which slots are processed; that is up to the runner.
+    /// For a run split over several runners, pass the span of the whole run.
+    pub fn with_slot_range(mut self, slot_range: Range<u64>) -> Self {
+        self.slot_range = Some(slot_range);
+        self
+    }
+
+    /// Marks the runner this plugin is given to as one of several that make
+    /// up a run, such as one per slot range or `--reverse` chunk. Only the
+    /// `first` runner writes [`ControlEvent::RunStarted`], and only the
+    /// `last` writes [`ControlEvent::RunFinished`], logs the exit report and
+    /// checks [`Self::with_strict_mint`]. Every runner still writes out what
+    /// it holds for its blocks and flushes the sinks at exit. A plugin is its
+    /// own run, both first and last, by default.
+    pub fn with_run_segment(mut self, first: bool, last: bool) -> Self {
+        self.first_segment = first;
+        self.last_segment = last;
+        self
+    }
+
+    /// Processes but does not emit events from the `warmup_slots` slots
+    /// starting at `first_slot`, since the first slots a stream delivers can
+    /// be incomplete. State such as the average price and holder set is still
+    /// updated from them, and suppressed events are not recorded in the dedup
+    /// store. These are real events that are lost to the output.
+    ///
+    /// Pass the first slot of the whole run rather than of each runner's
+    /// range, so a run split over several ranges warms up only once.
+    pub fn with_warmup_slots(mut self, first_slot: u64, warmup_slots: u64) -> Self {
+        self.warmup = first_slot..first_slot.saturating_add(warmup_slots);
+        self
+    }
+
+    /// Adds a sink that receives every decoded trade event after transforms
+    /// have run.
+    pub fn with_sink(mut self, sink: Arc<dyn TradeSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Replaces the instruction decoder, for example with one generated from a
+    /// newer IDL of the program. The plugin interprets the decoded
+    /// [`PumpfunInstruction`]s as before, so the decoder must map into that
+    /// type; instructions are still only passed to it when their data starts
+    /// with the discriminator of an event the plugin emits.
+    pub fn with_decoder(mut self, decoder: Arc<PumpfunInstructionDecoder>) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    /// Adds a transform that can enrich or redact each event before it is
+    /// written to the sinks. Transforms run in registration order.
+    pub fn with_transform(mut self, transform: TradeEventTransform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Also emits a [`MigrationEvent`] to the sinks when a token graduates.
+    pub fn with_track_migrations(mut self, track_migrations: bool) -> Self {
+        self.track_migrations = track_migrations;
+        self
+    }
+
+    /// Also emits a [`NewHolderEvent`] when an associated token account of the
+    /// tracked mint is created for an owner that held none of it before.
+    pub fn with_track_holders(mut self, track_holders: bool) -> Self {
+        self.track_holders = track_holders;
+        self
+    }
+
+    /// Also emits a [`TransferEvent`] for every SPL token `Transfer` and
+    /// `TransferChecked` of the tracked mint within matched transactions,
+    /// including the transfers a trade itself makes.
+    pub fn with_track_transfers(mut self, track_transfers: bool) -> Self {
+        self.track_transfers = track_transfers;
+        self
+    }
+
+    /// Attaches a per-mint exponentially time-weighted average price to each
+    /// trade, decaying with `half_life`. See [`crate::utils::ewma`] for the
+    /// formula.
+    pub fn with_price_half_life(mut self, half_life: Duration) -> Self {
+        self.prices = Some(Arc::new(Mutex::new(TimeDecayedAverage::new(half_life))));
+        self
+    }
+
+    /// Sets [`OwnedTradeEvent::block_time`] on trades written to the sinks.
+    ///
+    /// The firehose reports a block only after all of its transactions, so
+    /// trades are held per slot and written once the block arrives in
+    /// `on_block`, before transforms run. The processor callback still runs
+    /// immediately, without a block time. Trades still held at exit are
+    /// written without one.
+    ///
+    /// Also sets [`OwnedTradeEvent::processing_latency_ms`], the delay from
+    /// the block time to when the transaction was processed, and logs its
+    /// distribution at exit.
+    pub fn with_block_time(mut self, block_time: bool) -> Self {
+        self.block_time = block_time;
+        self.hold_by_slot()
+    }
+
+    /// Holds trades per slot exactly while [`Self::with_block_time`],
+    /// [`Self::with_detect_sandwiches`] or [`TimestampSource::Block`] asks
+    /// for it, so turning one off leaves the others in effect.
+    fn hold_by_slot(mut self) -> Self {
+        let hold = self.block_time
+            || self.sandwiches.is_some()
+            || self.timestamp_source == TimestampSource::Block;
+        if !hold {
+            self.pending_by_slot = None;
+        } else if self.pending_by_slot.is_none() {
+            self.pending_by_slot = Some(Arc::default());
+        }
+        self
+    }
+
+    /// Only emits trades whose payer is the creator of the traded mint, as
+    /// learned from the mint's create event. Trades are dropped until that
+    /// create event has been seen, so the range must include the token's
+    /// creation; with several threads, trades processed before the thread
+    /// holding the create event reaches it are dropped as well.
+    pub fn with_only_creator(mut self, only_creator: bool) -> Self {
+        self.creators = only_creator.then(Arc::default);
+        self
+    }
+
+    /// Skips decoding instructions whose data is larger than `max_bytes`,
+    /// logging a warning, to bound the work spent on malformed input.
+    /// Defaults to [`DEFAULT_MAX_INSTRUCTION_DATA`].
+    pub fn with_max_instruction_data(mut self, max_bytes: usize) -> Self {
+        self.max_instruction_data = max_bytes;
+        self
+    }
+
+    /// Writes the transaction with `signature` to `path` as a
+    /// [`TransactionBundle`] when it is seen, before it is decoded, so it can
+    /// be replayed with [`crate::utils::bundle::replay_transaction`].
+    pub fn with_export_transaction(
+        mut self,
+        signature: Signature,
+        path: impl Into<PathBuf>,
+    ) -> Self {
+        self.export = Some((signature, path.into()));
+        self
+    }
+
+    /// Writes every matched transaction, serialized and base64 encoded, to
+    /// `writer` as JSON lines. See [`crate::utils::raw_archive`] for the
+    /// format.
+    #[cfg(feature = "archive")]
+    pub fn with_raw_archive(mut self, writer: Box<dyn Write + Send>) -> Self {
+        self.raw_archive = Some(Arc::new(RawArchive::new(writer)));
+        self
+    }
+
+    /// Tracks the slots processed so far and applies `policy` to slots the
+    /// firehose delivers again. The number of repeated slots is reported in
+    /// `on_exit`. [`DuplicateSlotPolicy::Process`] disables tracking.
+    pub fn with_duplicate_slots(mut self, policy: DuplicateSlotPolicy) -> Self {
+        self.slot_tracker =
+            (policy != DuplicateSlotPolicy::Process).then(|| Arc::new(SlotTracker::new(policy)));
+        self
+    }
+
+    /// Writes one [`BlockSummary`] per block with trades of the tracked mint
+    /// instead of the individual trades. Trades are summed per slot as they
+    /// are decoded, in whatever order the block's transactions arrive, and the
+    /// summary is written when the block itself arrives. Trades of other mints
+    /// in the same transactions are left out.
+    pub fn with_block_summary(mut self, block_summary: bool) -> Self {
+        self.block_summaries = block_summary.then(Arc::default);
+        self
+    }
+
+    /// Sums the trades of the tracked mint per payer instead of writing them,
+    /// for [`Self::write_net_positions`] to write one [`NetPosition`] per
+    /// payer. With `max_payers`, payers beyond the first that many are not
+    /// tracked, bounding memory; their trades are counted and reported.
+    pub fn with_net_positions(mut self, net_positions: bool, max_payers: Option<usize>) -> Self {
+        self.net_positions = net_positions.then(|| {
+            Arc::new(Mutex::new(NetPositions {
+                max_payers,
+                ..Default::default()
+            }))
+        });
+        self
+    }
+
+    /// Emits a [`VwapSnapshot`] per mint for every `window` of trades. See
+    /// [`crate::utils::vwap`] for how windows are aligned and closed.
+    pub fn with_vwap_window(mut self, window: Duration) -> Self {
+        self.vwap = Some(Arc::new(Mutex::new(VwapWindows::new(window.as_secs()))));
+        self
+    }
+
+    /// Switches to cross-mint breadth mode: every transaction touching the
+    /// pumpfun program is decoded, and a [`BreadthSnapshot`] with the number of
+    /// distinct mints traded is emitted for every `slots` slots. Trades of
+    /// mints other than the tracked one only count towards breadth and are not
+    /// emitted. See [`crate::utils::breadth`] for how windows close.
+    pub fn with_breadth_window(mut self, slots: u64) -> Self {
+        self.breadth = Some(Arc::new(Mutex::new(BreadthWindows::new(slots))));
+        self
+    }
+
+    /// Whether to track `mint`. Breadth mode can run without a mint: with
+    /// `false`, transactions are matched on the pumpfun program alone rather
+    /// than also on `mint`, which is then a placeholder, and no trade,
+    /// holder, transfer or migration is emitted, leaving only the breadth
+    /// snapshots. Outside breadth mode nothing matches without a mint.
+    pub fn with_track_mint(mut self, track_mint: bool) -> Self {
+        self.track_mint = track_mint;
+        self
+    }
+
+    /// Records the time between consecutive trades of each mint and logs
+    /// the histograms at exit. See [`crate::utils::interarrival`] for the
+    /// buckets and why out-of-order trades make them approximate.
+    pub fn with_interarrival(mut self, interarrival: bool) -> Self {
+        self.interarrival = interarrival.then(Arc::default);
+        self
+    }
+
+    /// Accumulates the SOL volume traded on every mint and logs the `n` with
+    /// the most at exit. Only meaningful in breadth mode (see
+    /// [`Self::with_breadth_window`]), where trades of every mint are decoded;
+    /// otherwise the tracked mint is the only one seen, apart from mints that
+    /// happen to share its transactions. No ranking is logged when fewer than
+    /// two mints traded. `0` disables it.
+    pub fn with_top_mints(mut self, n: usize) -> Self {
+        self.top_mints = (n > 0).then(|| (n, Arc::default()));
+        self
+    }
+
+    /// Skips inner instructions entirely and only decodes top-level ones,
+    /// which saves extracting large CPI trees. Pumpfun's trade and create
+    /// events are self-CPI logs, so they are not seen in this mode.
+    pub fn with_top_level_only(mut self, top_level_only: bool) -> Self {
+        self.top_level_only = top_level_only;
+        self
+    }
+
+    /// Selects the time written to [`OwnedTradeEvent::timestamp`]; the
+    /// program timestamp, block time and ingest time stay available in their
+    /// own fields. [`TimestampSource::Block`] turns on
+    /// [`Self::with_block_time`], and trades whose block time is unknown keep
+    /// the program timestamp.
+    pub fn with_timestamp_source(mut self, source: TimestampSource) -> Self {
+        self.timestamp_source = source;
+        self.hold_by_slot()
+    }
+
+    /// Only emits events whose instruction stack height lies within
+    /// `min..=max`; a `None` bound is open. Top-level instructions have a
+    /// height of 1. Pumpfun trade events are self-CPI logs, so they are
+    /// typically inner instructions with a height above 1.
+    pub fn with_stack_height_range(mut self, min: Option<u32>, max: Option<u32>) -> Self {
+        self.min_stack_height = min;
+        self.max_stack_height = max;
+        self
+    }
+
+    /// Only emits trades whose [`TradeEvent::curve_progress`] lies within
+    /// `min..=max`; a `None` bound is open. Trades whose event carried no
+    /// reserves have no progress and are dropped, unless `pass_missing` is
+    /// set. Like [`Self::with_only_creator`] the check runs before the
+    /// processor callback.
+    pub fn with_curve_progress_range(
+        mut self,
+        min: Option<f64>,
+        max: Option<f64>,
+        pass_missing: bool,
+    ) -> Self {
+        self.min_curve_progress = min;
+        self.max_curve_progress = max;
+        self.pass_missing_curve_progress = pass_missing;
+        self
+    }
+
+    /// Emits every instruction of `program_id` in a matched transaction that
+    /// the decoder returns `None` for, with its raw data and accounts. Meant
+    /// for finding missing decoder variants; it can be very noisy.
+    pub fn with_dump_undecoded(mut self, program_id: Pubkey) -> Self {
+        self.dump_undecoded = Some(program_id);
+        self
+    }
+
+    /// Counts unique payers for the exit summary with a fixed-size
+    /// HyperLogLog sketch instead of an exact set, bounding memory to a few KB
+    /// at the cost of a ~1.6% standard error.
+    pub fn with_approx_unique(self, approx_unique: bool) -> Self {
+        *self
+            .payers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = if approx_unique {
+            PayerCounter::Approx(HyperLogLog::default())
+        } else {
+            PayerCounter::default()
+        };
+        self
+    }
+
+    /// Fails `on_exit` if no transaction involving the mint was observed,
+    /// which usually means a mistyped mint or a range before the token existed.
+    ///
+    /// The runner only logs `on_exit` errors, so callers that need a non-zero
+    /// exit code should also check [`Self::mint_seen`] after the run.
+    pub fn with_strict_mint(mut self, strict_mint: bool) -> Self {
+        self.strict_mint = strict_mint;
+        self
+    }
+
+    /// Attaches the accounts of the instruction that emitted each trade, with
+    /// their signer and writable flags, to trade events.
+    pub fn with_include_accounts(mut self, include_accounts: bool) -> Self {
+        self.include_accounts = include_accounts;
+        self
+    }
+
+    /// Annotates trades with the token symbol from its Metaplex metadata
+    /// account, looked up once per mint through `getAccountInfo` on `rpc_url`.
+    /// Trades of mints without metadata, or whose lookup failed, get no symbol.
+    #[cfg(feature = "metadata")]
+    pub fn with_enrich_metadata(mut self, rpc_url: impl Into<String>) -> Self {
+        self.metadata = Some(Arc::new(MetadataEnricher::new(
+            rpc_url.into(),
+            self.enrich_concurrency,
+        )));
+        self
+    }
+
+    /// Limits how many metadata lookups run at once, [`DEFAULT_ENRICH_CONCURRENCY`]
+    /// by default. Lookups of the same mint are coalesced regardless.
+    #[cfg(feature = "metadata")]
+    pub fn with_enrich_concurrency(mut self, limit: usize) -> Self {
+        self.enrich_concurrency = limit.max(1);
+        if let Some(metadata) = &self.metadata {
+            self.metadata = Some(Arc::new(MetadataEnricher::with_lookup(
+                metadata.lookup.clone(),
+                self.enrich_concurrency,
+            )));
+        }
+        self
+    }
+
+    /// Whether `trade` passes [`Self::with_only_creator`].
+    fn creator_allowed(&self, trade: &TradeEventData) -> bool {
+        let Some(creators) = &self.creators else {
+            return true;
+        };
+        creators
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&trade.mint.to_string())
+            .is_some_and(|creator| *creator == trade.user.to_string())
+    }
+
+    /// Whether `trade` passes [`Self::with_curve_progress_range`].
+    fn curve_progress_allowed(&self, trade: &TradeEventData) -> bool {
+        if self.min_curve_progress.is_none() && self.max_curve_progress.is_none() {
+            return true;
+        }
+        match curve_progress(trade.virtual_token_reserves) {
+            Some(progress) => {
+                self.min_curve_progress.is_none_or(|min| progress >= min)
+                    && self.max_curve_progress.is_none_or(|max| progress <= max)
+            }
+            None => self.pass_missing_curve_progress,
+        }
+    }
+
+    /// Adds the trade to its mint's VWAP window, writing the snapshot of a
+    /// window it closes.
+    fn update_vwap(&self, trade: &TradeEventData) {
+        let Some(vwap) = &self.vwap else {
+            return;
+        };
+        let closed = vwap
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .add(
+                &trade.mint.to_string(),
+                trade.timestamp,
+                trade.sol_amount,
+                trade.token_amount,
+            );
+        if let Some(snapshot) = closed {
+            self.write_vwap(&snapshot);
+        }
+    }
+
+    fn write_vwap(&self, snapshot: &VwapSnapshot) {
+        for sink in &self.sinks {
+            if let Err(err) = sink.write_vwap(snapshot) {
+                error!(
+                    "Failed to write VWAP snapshot for {}: {}",
+                    snapshot.mint, err
+                );
+            }
+        }
+    }
+
+    /// Adds the trade to its mint's volume for the exit ranking.
+    fn record_volume(&self, trade: &TradeEventData) {
+        if let Some((_, volumes)) = &self.top_mints {
+            volumes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .record(&trade.mint.to_string(), trade.sol_amount);
+        }
+    }
+
+    /// Records the trade's mint in its breadth window.
+    fn record_breadth(&self, trade: &TradeEventData, slot: u64) {
+        if let Some(breadth) = &self.breadth {
+            breadth
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .record(slot, trade.mint.to_bytes());
+        }
+    }
+
+    fn record_interarrival(&self, trade: &TradeEventData) {
+        if let Some(interarrival) = &self.interarrival {
+            interarrival
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .record(&trade.mint.to_string(), trade.timestamp);
+        }
+    }
+
+    /// Whether the trade is of the tracked mint. Outside breadth mode every
+    /// decoded trade is, since transactions are matched on the mint.
+    fn tracks(&self, trade: &TradeEventData) -> bool {
+        self.breadth.is_none() || (self.track_mint && trade.mint.to_bytes() == self.mint.to_bytes())
+    }
+
+    /// Writes the summary of `slot`, if it had trades.
+    fn write_block_summary(&self, slot: u64, block_time: Option<i64>) -> Result<(), ProcessError> {
+        let Some(summaries) = &self.block_summaries else {
+            return Ok(());
+        };
+        let summary = summaries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&slot);
+        let Some(mut summary) = summary else {
+            return Ok(());
+        };
+        if self.warming_up(slot) {
+            return Ok(());
+        }
+        summary.block_time = block_time;
+        self.write_sinks("block summary", &format!("slot {slot}"), |sink| {
+            sink.write_block_summary(&summary)
+        })
+    }
+
+    /// Writes a run marker to every sink.
+    fn write_control(&self, event: &ControlEvent) -> Result<(), ProcessError> {
+        let name = match event {
+            ControlEvent::RunStarted { .. } => "run started",
+            ControlEvent::RunFinished { .. } => "run finished",
+        };
+        self.write_sinks("run marker", name, |sink| sink.write_control(event))
+    }
+
+    fn write_breadth(&self, snapshot: &BreadthSnapshot) {
+        for sink in &self.sinks {
+            if let Err(err) = sink.write_breadth(snapshot) {
+                error!(
+                    "Failed to write breadth snapshot for slot {}: {}",
+                    snapshot.window_start_slot, err
+                );
+            }
+        }
+    }
+
+    /// Folds the trade's price into its mint's average and returns the result.
+    /// Trades without tokens or SOL have no price and leave the average as is.
+    fn update_price(&self, trade: &TradeEventData) -> Option<f64> {
+        let prices = self.prices.as_ref()?;
+        let mut prices = prices
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mint = trade.mint.to_string();
+        if trade.token_amount == 0 || trade.sol_amount == 0 {
+            return prices.get(&mint);
+        }
+        // Pumpfun mints use 6 decimals.
+        let price = (trade.sol_amount as f64 / 1e9) / (trade.token_amount as f64 / 1e6);
+        Some(prices.update(&mint, price, trade.timestamp))
+    }
+
+    #[cfg(feature = "metadata")]
+    fn symbol_for(&self, mint: &impl ToString) -> Option<String> {
+        let metadata = self.metadata.as_ref()?.cached(&mint.to_string())??;
+        Some(metadata.symbol)
+    }
+
+    #[cfg(not(feature = "metadata"))]
+    fn symbol_for(&self, _mint: &impl ToString) -> Option<String> {
+        None
+    }
+
+    /// Flushes every sink without ending the run.
+    ///
+    /// The jetstreamer [`Plugin`] trait only flushes through `on_exit`, so long
+    /// runs call this from the host on a timer to persist buffered output.
+    /// Clones share their sinks, so flushing any clone flushes the running one.
+    /// The dedup store is flushed last and only if every sink flushed, so it
+    /// never records an event that is not yet in the output.
+    pub fn flush(&self) -> std::io::Result<()> {
+        for sink in &self.sinks {
+            sink.flush()?;
+        }
+        if let Some(dedup) = &self.dedup {
+            dedup
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .flush()?;
+        }
+        #[cfg(feature = "archive")]
+        if let Some(archive) = &self.raw_archive {
+            archive.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Whether `slot` is in the warmup, counting the event of it that is
+    /// being suppressed if so.
+    fn warming_up(&self, slot: u64) -> bool {
+        let warming_up = self.warmup.contains(&slot);
+        if warming_up {
+            self.stats.warmup_suppressed.fetch_add(1, Ordering::Relaxed);
+        }
+        warming_up
+    }
+
+    /// Whether the event is past the warmup and has not been emitted before
+    /// according to the dedup store. Without a store every event is new.
+    fn should_emit(&self, slot: u64, signature: &str, instruction_path: &str) -> bool {
+        if self.warming_up(slot) {
+            return false;
+        }
+        let Some(dedup) = &self.dedup else {
+            return true;
+        };
+        let seen = dedup
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(signature, instruction_path);
+        if seen {
+            self.stats.deduplicated.fetch_add(1, Ordering::Relaxed);
+        }
+        !seen
+    }
+
+    /// Records an event every sink has written in the dedup store. Once the
+    /// store has [`DEDUP_FLUSH_BYTES`] buffered, flushes the sinks and then
+    /// the store, failing like a sink if either does.
+    fn record_emitted(
+        &self,
+        slot: u64,
+        signature: &str,
+        instruction_path: &str,
+    ) -> Result<(), ProcessError> {
+        let Some(dedup) = &self.dedup else {
+            return Ok(());
+        };
+        let pending = {
+            let mut dedup = dedup
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            dedup.insert(slot, signature, instruction_path);
+            dedup.pending_bytes()
+        };
+        if pending < DEDUP_FLUSH_BYTES {
+            return Ok(());
+        }
+        self.flush().map_err(|err| {
+            error!("Failed to flush the dedup store: {}", err);
+            ProcessError::SinkFailed {
+                signature: signature.to_string(),
+                event: "dedup store",
+                reason: err.to_string(),
+            }
+        })
+    }
+
+    /// Runs the embedded fixture transactions (see [`crate::utils::self_test`])
+    /// through a plain plugin using this plugin's decoder and checks that each
+    /// produces its expected trade. Returns the number of fixtures checked, or
+    /// a description of the first mismatch, for example after upgrading
+    /// `carbon-pumpfun-decoder` to a version with a different event layout.
+    pub fn self_test(&self) -> Result<usize, String> {
+        let fixtures = fixtures();
+        for fixture in &fixtures {
+            let trades = Arc::new(Mutex::new(Vec::new()));
+            let collected = trades.clone();
+            let plugin = Self::with_processor(
+                FIXTURE_MINT,
+                Arc::new(move |event: &TradeEvent| {
+                    collected
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(OwnedTradeEvent::from(event));
+                }),
+            )
+            .with_decoder(self.decoder.clone());
+            plugin
+                .handle_transaction(&fixture.transaction)
+                .map_err(|err| format!("fixture {}: {}", fixture.name, err))?;
+            let trades = trades
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let [trade] = trades.as_slice() else {
+                return Err(format!(
+                    "fixture {}: expected 1 trade, decoded {}",
+                    fixture.name,
+                    trades.len()
+                ));
+            };
+            let expected = &fixture.expected;
+            let (sol_amount, token_amount) = if trade.is_buy {
+                (trade.amount_in, trade.amount_out)
+            } else {
+                (trade.amount_out, trade.amount_in)
+            };
+            if trade.mint != FIXTURE_MINT.to_string()
+                || trade.is_buy != expected.is_buy
+                || sol_amount != expected.sol_amount
+                || token_amount != expected.token_amount
+            {
+                return Err(format!(
+                    "fixture {}: decoded {} {} lamports / {} tokens of {}, expected {} {} lamports / {} tokens",
+                    fixture.name,
+                    if trade.is_buy { "buy" } else { "sell" },
+                    sol_amount,
+                    token_amount,
+                    trade.mint,
+                    if expected.is_buy { "buy" } else { "sell" },
+                    expected.sol_amount,
+                    expected.token_amount
+                ));
+            }
+        }
+        Ok(fixtures.len())
+    }
+
+    /// Returns whether any transaction involving the mint has been observed.
+    pub fn mint_seen(&self) -> bool {
+        self.stats.mint_seen.load(Ordering::Relaxed)
+    }
+
+    /// Stops processing transactions until [`resume`](Self::resume) is
+    /// called, on every clone of the plugin. Transactions seen while paused
+    /// are dropped, not buffered; blocks still advance the last slot.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes processing after [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns whether processing is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Returns the highest slot whose block has been observed.
+    pub fn last_slot(&self) -> u64 {
+        self.stats.last_slot.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of events emitted so far.
+    pub fn events(&self) -> u64 {
+        self.stats.matches.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of transactions dropped while paused.
+    pub fn paused_transactions(&self) -> u64 {
+        self.stats.paused_transactions.load(Ordering::Relaxed)
+    }
+
+    fn stack_height_allowed(&self, stack_height: u32) -> bool {
+        self.min_stack_height.is_none_or(|min| stack_height >= min)
+            && self.max_stack_height.is_none_or(|max| stack_height <= max)
+    }
+
+    /// Logs "processed up to slot X, N matches so far" every `interval` during
+    /// stretches where no trade events are emitted.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(Arc::new(Heartbeat::new(interval)));
+        self
+    }
+
+    /// Writes `{"last_slot", "events", "uptime_secs", "updated_at"}` as JSON to
+    /// `path` on load, at most once per `interval` while blocks
+    /// arrive, and at exit. A supervisor can treat a stale `updated_at` as a
+    /// stall.
+    pub fn with_status_file(mut self, path: impl Into<PathBuf>, interval: Duration) -> Self {
+        self.status_file = Some(Arc::new(StatusFile {
+            path: path.into(),
+            interval,
+            started: Instant::now(),
+            last_write: Mutex::new(None),
+        }));
+        self
+    }
+
+    /// Logs and records transactions that fail or panic during decoding instead
+    /// of aborting the run. Skipped items are reported in `on_exit`.
+    pub fn with_skip_errors(mut self, skip_errors: bool) -> Self {
+        self.skip_errors = skip_errors;
+        self
+    }
+
+    /// Checks each decoded trade against the trader's token balance delta from
+    /// the transaction metadata, warning when they differ by more than
+    /// `tolerance` raw units. A mismatch rate is reported in `on_exit`.
+    pub fn with_verify_balances(mut self, tolerance: u64) -> Self {
+        self.balance_verifier = Some(Arc::new(BalanceVerifier {
+            tolerance,
+            ..Default::default()
+        }));
+        self
+    }
+
+    /// Checks each trade's decoded direction against the movement of its
+    /// mint's virtual SOL reserves since the mint's previous trade, warning
+    /// on and flagging disagreements in [`TradeEvent::direction_mismatch`].
+    /// The mismatch rate is reported in `on_exit`. Trades are compared in
+    /// processing order, so run with a single thread for exact results.
+    pub fn with_check_direction(mut self, check_direction: bool) -> Self {
+        self.direction_checker = check_direction.then(Arc::default);
+        self
+    }
+
+    /// Flags trades that are part of a sandwich, a buy and a sell by one
+    /// payer right around another payer's buy of the same mint in the same
+    /// block, in [`OwnedTradeEvent::sandwich_role`]. Turns on
+    /// [`Self::with_block_time`], so trades are held until their block
+    /// arrives and checked together; see [`crate::utils::sandwich`] for the
+    /// heuristic and its limits. The number found is logged at exit.
+    pub fn with_detect_sandwiches(mut self, detect_sandwiches: bool) -> Self {
+        self.sandwiches = detect_sandwiches.then(Arc::default);
+        self.hold_by_slot()
+    }
+
+    /// Replaces the wallet addresses of every event with their HMAC-SHA256
+    /// under `key` (see [`AddressHasher`]) just before it is written, so the
+    /// output can be shared without revealing who traded while the rows of
+    /// one wallet still correlate. Mints and amounts stay in the clear.
+    ///
+    /// Hashes the payer of trades and net positions, the owner and token
+    /// account of new holders, the token accounts, owners and authority of
+    /// transfers and the user of migrations. The processor callback, the
+    /// instruction accounts of [`Self::with_include_accounts`], undecoded
+    /// instruction dumps and raw archives still see the clear addresses.
+    pub fn with_hash_payers(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.address_hasher = Some(AddressHasher::new(key));
+        self
+    }
+
+    /// Adds a filter that transactions must pass before they are decoded,
+    /// after the filters added before it. See [`crate::filters`] for the
+    /// built-in ones.
+    pub fn with_filter(mut self, filter: impl TransactionFilter + 'static) -> Self {
+        self.filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Only processes transactions whose fee payer (the first account key) is
+    /// in `fee_payers`. The check runs before any decoding, so transactions
+    /// from other payers cost a single set lookup, prefiltered by a Bloom
+    /// filter for large sets (see [`crate::utils::bloom`]). An empty set
+    /// disables it.
+    pub fn with_fee_payer_filter(self, fee_payers: HashSet<Pubkey>) -> Self {
+        if fee_payers.is_empty() {
+            return self;
+        }
+        self.with_filter(FeePayerIn::new(fee_payers))
+    }
+
+    /// Skips transactions that reference any of `programs` in their account
+    /// keys, including addresses loaded from lookup tables, even if they touch
+    /// the mint. Like [`Self::with_fee_payer_filter`] it runs before any
+    /// decoding. An empty set disables it.
+    pub fn with_excluded_programs(self, programs: HashSet<Pubkey>) -> Self {
+        if programs.is_empty() {
+            return self;
+        }
+        self.with_filter(ExcludePrograms::new(programs))
+    }
+
+    /// Suppresses events already recorded in `store`, typically by an earlier
+    /// run over overlapping slots, and records every event all sinks wrote
+    /// in it; an event a sink failed to write is emitted again on resume. See
+    /// [`crate::utils::dedup`] for the store format and pruning.
+    pub fn with_dedup_store(mut self, store: DedupStore) -> Self {
+        self.dedup = Some(Arc::new(Mutex::new(store)));
+        self
+    }
+
+    /// Writes the positions summed under [`Self::with_net_positions`], largest
+    /// absolute token change first, and starts over. Not called by `on_exit`,
+    /// since a run may span several runners; call it once every range is done.
+    pub fn write_net_positions(&self) -> Result<(), ProcessError> {
+        let Some(positions) = &self.net_positions else {
+            return Ok(());
+        };
+        let (payers, untracked, max_payers) = {
+            let mut positions = positions
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            (
+                std::mem::take(&mut positions.payers),
+                std::mem::take(&mut positions.untracked),
+                positions.max_payers,
+            )
+        };
+        if untracked > 0 {
+            warn!(
+                "{} trade(s) of payers beyond the first {} were left out of the net positions",
+                untracked,
+                max_payers.unwrap_or_default()
+            );
+        }
+        let mut ranked: Vec<NetPosition> = payers.into_values().collect();
+        if let Some(hasher) = &self.address_hasher {
+            for position in &mut ranked {
+                position.payer = hasher.hash(&position.payer);
+            }
+        }
+        ranked.sort_unstable_by(|a, b| {
+            b.net_tokens
+                .unsigned_abs()
+                .cmp(&a.net_tokens.unsigned_abs())
+                .then_with(|| a.payer.cmp(&b.payer))
+        });
+        let mut result = Ok(());
+        for position in &ranked {
+            let written = self.write_sinks("net position", &position.payer, |sink| {
+                sink.write_net_position(position)
+            });
+            if result.is_ok() {
+                result = written;
+            }
+        }
+        result
+    }
+
+    /// Runs `transaction` through the same filters, decoding and transforms
+    /// as [`Plugin::on_transaction`] and returns the trade events it would
+    /// write, for using the plugin as a decoding library without a runner.
+    ///
+    /// The events are collected before any diversion: they are returned even
+    /// with [`Self::with_net_positions`] or [`Self::with_block_summary`], and
+    /// right away with [`Self::with_block_time`], so their `block_time` is
+    /// `None`. The plugin's state is read but not updated: the run
+    /// statistics, dedup store, accumulators and sinks are left untouched,
+    /// and the warmup is checked without counting. Only the processor
+    /// callback still runs. Pausing, duplicate slot tracking and
+    /// [`Self::with_skip_errors`] do not apply: failures are returned.
+    pub fn process_transaction(
+        &self,
+        transaction: &TransactionData,
+    ) -> Result<Vec<OwnedTradeEvent>, ProcessError> {
+        let collector = Arc::new(CollectSink::default());
+        let mut plugin = self.detached();
+        plugin.sinks = vec![collector.clone()];
+        plugin.handle_transaction(transaction)?;
+        let events = std::mem::take(
+            &mut *collector
+                .events
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        Ok(events)
+    }
+
+    /// Returns a copy of the plugin with the same configuration whose state is
+    /// its own: accumulators and counters start empty, running values such as
+    /// average prices are snapshots, and nothing is written outside it.
+    fn detached(&self) -> Self {
+        let mut plugin = self.clone();
+        plugin.stats = Arc::default();
+        plugin.paused = Arc::default();
+        plugin.heartbeat = None;
+        plugin.status_file = None;
+        plugin.skipped = Arc::default();
+        plugin.balance_verifier = self.balance_verifier.as_ref().map(|verifier| {
+            Arc::new(BalanceVerifier {
+                tolerance: verifier.tolerance,
+                ..Default::default()
+            })
+        });
+        plugin.direction_checker = self.direction_checker.as_ref().map(|checker| {
+            Arc::new(DirectionChecker {
+                last_reserves: Mutex::new(
+                    checker
+                        .last_reserves
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .clone(),
+                ),
+                ..Default::default()
+            })
+        });
+        plugin.sandwiches = None;
+        plugin.sinks = Vec::new();
+        plugin.payers = Arc::default();
+        plugin.dedup = None;
+        plugin.prices = self.prices.as_ref().map(|prices| {
+            Arc::new(Mutex::new(
+                prices
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .clone(),
+            ))
+        });
+        plugin.pending_by_slot = None;
+        plugin.latencies = Arc::default();
+        plugin.block_summaries = None;
+        plugin.net_positions = None;
+        plugin.slot_tracker = None;
+        plugin.creators = self.creators.as_ref().map(|creators| {
+            Arc::new(Mutex::new(
+                creators
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .clone(),
+            ))
+        });
+        plugin.export = None;
+        #[cfg(feature = "archive")]
+        {
+            plugin.raw_archive = None;
+        }
+        plugin.vwap = None;
+        // Breadth mode changes which transactions match, so it stays on, but
+        // records into windows of its own.
+        plugin.breadth = self
+            .breadth
+            .as_ref()
+            .map(|_| Arc::new(Mutex::new(BreadthWindows::new(1))));
+        plugin.interarrival = None;
+        plugin.top_mints = None;
+        plugin
+    }
+
+    /// Logs the end-of-run statistics and the reports of the enabled checks.
+    fn log_run_report(&self) {
+        info!(
+            "Processed up to slot {}: {} matches, {}",
+            self.stats.last_slot.load(Ordering::Relaxed),
+            self.stats.matches.load(Ordering::Relaxed),
+            self.payers
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .summary()
+        );
+        let paused_transactions = self.paused_transactions();
+        if paused_transactions > 0 {
+            warn!(
+                "Dropped {} transaction(s) while processing was paused",
+                paused_transactions
+            );
+        }
+        let inferred_stack_heights = self.stats.inferred_stack_heights.load(Ordering::Relaxed);
+        if inferred_stack_heights > 0 {
+            warn!(
+                "{} transaction(s) had inner instructions without stack heights; \
+                 their nesting and instruction paths are approximate",
+                inferred_stack_heights
+            );
+        }
+        let warmup_suppressed = self.stats.warmup_suppressed.load(Ordering::Relaxed);
+        if warmup_suppressed > 0 {
+            info!(
+                "Suppressed {} event(s) from the first {} warmup slot(s)",
+                warmup_suppressed,
+                self.warmup.end - self.warmup.start
+            );
+        }
+        if let Some(dedup) = &self.dedup {
+            let dedup = dedup
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            info!(
+                "Suppressed {} event(s) already in the dedup store; {} stored in {}",
+                self.stats.deduplicated.load(Ordering::Relaxed),
+                dedup.len(),
+                dedup.path().display()
+            );
+        }
+        #[cfg(feature = "archive")]
+        if let Some(archive) = &self.raw_archive {
+            info!("Archived {} raw transaction(s)", archive.written());
+        }
+        if let Some(verifier) = &self.balance_verifier {
+            verifier.report();
+        }
+        if let Some(checker) = &self.direction_checker {
+            checker.report();
+        }
+        if let Some(sandwiches) = &self.sandwiches {
+            info!(
+                "Found {} sandwich(es) among the emitted trades",
+                sandwiches.load(Ordering::Relaxed)
+            );
+        }
+        if let Some(interarrival) = &self.interarrival {
+            let interarrival = interarrival
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            for (mint, histogram) in interarrival.iter() {
+                info!(
+                    "Inter-arrival times for {} over {} gap(s): {}",
+                    mint,
+                    histogram.gaps(),
+                    histogram
+                );
+                if histogram.out_of_order() > 0 {
+                    warn!(
+                        "{} trade(s) of {} arrived out of order; the histogram is approximate",
+                        histogram.out_of_order(),
+                        mint
+                    );
+                }
+            }
+        }
+        if let Some((n, volumes)) = &self.top_mints {
+            let volumes = volumes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if volumes.len() > 1 {
+                info!("Top {} of {} mint(s) by SOL volume:", n, volumes.len());
+                for (rank, (mint, volume)) in volumes.top(*n).into_iter().enumerate() {
+                    info!("{:>4}. {} {}", rank + 1, mint, volume);
+                }
+            } else {
+                info!("Fewer than two mints traded; no top mints to rank");
+            }
+        }
+        let latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if latencies.count() > 0 {
+            info!(
+                "Processing latency over {} trade(s): {}",
+                latencies.count(),
+                latencies
+            );
+        }
+    }
+
+    /// Groups the errors recovered from so far by kind: transactions skipped
+    /// under [`Self::with_skip_errors`], decoder panics, oversized
+    /// instructions and duplicate slots. Logged at exit.
+    pub fn error_report(&self) -> ErrorReport {
+        let mut report = ErrorReport::new();
+        let skipped = self.skipped();
+        for kind in SkipKind::ALL {
+            let of_kind = skipped.iter().filter(|item| item.kind == kind);
+            report.add(
+                kind.name(),
+                of_kind.clone().count() as u64,
+                of_kind
+                    .map(|item| format!("slot {} {}: {}", item.slot, item.signature, item.reason)),
+            );
+        }
+        report.add(
+            "decoder panics (treated as undecodable)",
+            self.stats.decoder_panics.load(Ordering::Relaxed),
+            [],
+        );
+        report.add(
+            &format!(
+                "instructions over the {} byte limit (not decoded)",
+                self.max_instruction_data
+            ),
+            self.stats.oversized.load(Ordering::Relaxed),
+            [],
+        );
+        if let Some(tracker) = &self.slot_tracker {
+            tracker.report(&mut report);
+        }
+        report
+    }
+
+    /// Returns the transactions skipped so far under [`Self::with_skip_errors`].
+    pub fn skipped(&self) -> Vec<SkippedTransaction> {
+        self.skipped
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Returns whether `transaction` passes the plugin's filters, without
+    /// decoding it or touching any sink.
+    pub fn matches(&self, transaction: &TransactionData) -> bool {
+        if !self
+            .filters
+            .iter()
+            .all(|filter| filter.matches(transaction))
+        {
+            return false;
+        }
+
+        // Check if the mint address is involved in any instruction
+        !transaction.transaction.message.instructions().is_empty()
+            && ((self.track_mint && MintPresent(self.mint).matches(transaction))
+                || (self.breadth.is_some()
+                    && ProgramPresent(PUMPFUN_PROGRAM_ID).matches(transaction)))
+    }
+
+    /// Decodes a single transaction and emits any trade events it contains.
+    fn handle_transaction(&self, transaction: &TransactionData) -> Result<(), ProcessError> {
+        let _context = panic_context::enter(transaction.slot, transaction.signature);
+        if self.matches(transaction) {
+            if !self.top_level_only {
+                check_inner_instructions(transaction)?;
+                if has_missing_stack_heights(&transaction.transaction_status_meta) {
+                    self.stats
+                        .inferred_stack_heights
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            // In breadth mode most matched transactions are of other mints.
+            if self.track_mint
+                && (self.breadth.is_none() || MintPresent(self.mint).matches(transaction))
+            {
+                self.stats.mint_seen.store(true, Ordering::Relaxed);
+                info!("Mint involved in transaction: {:?}", transaction.signature);
+            }
+            #[cfg(feature = "archive")]
+            if let Some(archive) = &self.raw_archive {
+                archive
+                    .write(
+                        transaction.slot,
+                        &transaction.signature,
+                        &transaction.transaction,
+                    )
+                    .map_err(|err| ProcessError::SinkFailed {
+                        signature: transaction.signature.to_string(),
+                        event: "raw transaction",
+                        reason: err.to_string(),
+                    })?;
+            }
+
+            // Create TransactionMetadata from transaction data
+            let transaction_metadata = Arc::new(TransactionMetadata {
+                slot: transaction.slot,
+                signature: transaction.signature,
+                fee_payer: transaction.transaction.message.static_account_keys()[0],
+                meta: transaction.transaction_status_meta.clone(),
+                message: transaction.transaction.message.clone(),
+                block_time: None,
+            });
+
+            // Extract instructions with metadata using the transformers module
+            let extract = if self.top_level_only {
+                extract_top_level_instructions_with_metadata
+            } else {
+                extract_instructions_with_metadata
+            };
+            let instructions_with_metadata: InstructionsWithMetadata = extract(
+                &transaction_metadata,
+                &transaction.transaction.message,
+                &transaction.transaction_status_meta,
+            );
+
+            let nested_instructions = NestedInstructions::from_flat(instructions_with_metadata)
+                .map_err(|err| ProcessError::MetadataInvalid {
+                    signature: transaction.signature.to_string(),
+                    reason: err.to_string(),
+                })?;
+
+            // Process each instruction recursively
+            let decoder = self.decoder.as_ref();
+            let signature_str = transaction.signature.to_string();
+
+            let mut ancestors = Vec::new();
+            for nested_instruction in nested_instructions.iter() {
+                self.process(
+                    nested_instruction,
+                    &mut ancestors,
+                    &signature_str,
+                    transaction.slot,
+                    decoder,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the registered transforms on an owned copy of `event` and writes the
+    /// result to every sink.
+    fn dispatch(&self, event: &TradeEvent) -> Result<(), ProcessError> {
+        if let Some(positions) = &self.net_positions {
+            if event.mint == self.mint.to_string() {
+                positions
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .add(event);
+            }
+            return Ok(());
+        }
+        if let Some(summaries) = &self.block_summaries {
+            if event.mint == self.mint.to_string() {
+                summaries
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .entry(event.slot)
+                    .or_insert_with(|| BlockSummary {
+                        slot: event.slot,
+                        mint: event.mint.clone(),
+                        ..Default::default()
+                    })
+                    .add(event);
+            }
+            return Ok(());
+        }
+        if self.sinks.is_empty() {
+            return Ok(());
+        }
+        let mut owned = OwnedTradeEvent::from(event);
+        let processed_at = unix_millis();
+        owned.ingest_time = Some(processed_at.div_euclid(1000));
+        if let Some(pending) = &self.pending_by_slot {
+            pending
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .entry(owned.slot)
+                .or_default()
+                .push((owned, processed_at));
+            return Ok(());
+        }
+        self.emit(owned)
+    }
+
+    /// Sets the sandwich role of a block's held `trades`, in processing
+    /// order, with [`Self::with_detect_sandwiches`].
+    fn mark_sandwiches(&self, trades: &mut [(OwnedTradeEvent, i64)]) {
+        let Some(sandwiches) = &self.sandwiches else {
+            return;
+        };
+        let roles = detect_sandwiches(
+            &trades
+                .iter()
+                .map(|(trade, _)| SandwichTrade {
+                    mint: &trade.mint,
+                    payer: &trade.payer,
+                    is_buy: trade.is_buy,
+                })
+                .collect::<Vec<_>>(),
+        );
+        for ((trade, _), role) in trades.iter_mut().zip(roles) {
+            if role == Some(SandwichRole::Victim) {
+                sandwiches.fetch_add(1, Ordering::Relaxed);
+            }
+            trade.sandwich_role = role.map(|role| role.to_string());
+        }
+    }
+
+    /// Runs the transforms on `owned` and writes it to every sink.
+    fn emit(&self, mut owned: OwnedTradeEvent) -> Result<(), ProcessError> {
+        if !self.should_emit(owned.slot, &owned.signature, &owned.instruction_path) {
+            return Ok(());
+        }
+        owned.timestamp = match self.timestamp_source {
+            TimestampSource::Program => owned.program_timestamp,
+            TimestampSource::Block => owned.block_time.unwrap_or(owned.program_timestamp),
+            TimestampSource::Ingest => owned.ingest_time.unwrap_or(owned.program_timestamp),
+        };
+        for transform in &self.transforms {
+            transform(&mut owned);
+        }
+        if let Some(hasher) = &self.address_hasher {
+            owned.payer = hasher.hash(&owned.payer);
+        }
+        self.write_sinks("trade event", &owned.signature, |sink| {
+            sink.write_event(&owned)
+        })?;
+        self.record_emitted(owned.slot, &owned.signature, &owned.instruction_path)
+    }
+
+    /// Calls `write` on every sink, logging each failure, and returns the
+    /// first one as [`ProcessError::SinkFailed`].
+    fn write_sinks(
+        &self,
+        event: &'static str,
+        signature: &str,
+        write: impl Fn(&dyn TradeSink) -> io::Result<()>,
+    ) -> Result<(), ProcessError> {
+        let mut result = Ok(());
+        for sink in &self.sinks {
+            if let Err(err) = write(sink.as_ref()) {
+                error!("Failed to write {} {}: {}", event, signature, err);
+                if result.is_ok() {
+                    result = Err(ProcessError::SinkFailed {
+                        signature: signature.to_string(),
+                        event,
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    /// Cheap check on the leading discriminator bytes of `data`, so the full
+    /// decoder only runs on instructions that can be a variant the plugin
+    /// emits. Disabled while dumping undecoded instructions, which needs every
+    /// instruction decoded.
+    fn may_decode(&self, data: &[u8]) -> bool {
+        self.dump_undecoded.is_some() || self.emits(data)
+    }
+
+    /// Whether `data` starts with the discriminator of an event the plugin
+    /// emits.
+    fn emits(&self, data: &[u8]) -> bool {
+        data.starts_with(TradeEventData::DISCRIMINATOR)
+            || (self.track_migrations
+                && data.starts_with(CompletePumpAmmMigrationEvent::DISCRIMINATOR))
+            || (self.creators.is_some() && data.starts_with(CreateEvent::DISCRIMINATOR))
+    }
+
+    /// Decodes a single instruction, treating a decoder panic as undecodable
+    /// so one malformed instruction does not take down the whole thread. An
+    /// emitted event that fails to decode is a [`ProcessError::DecodeFailed`].
+    fn decode(
+        &self,
+        nested_instruction: &NestedInstruction,
+        transaction_signature: &str,
+        decoder: &PumpfunInstructionDecoder,
+    ) -> Result<Option<DecodedInstruction<PumpfunInstruction>>, ProcessError> {
+        let data_len = nested_instruction.instruction.data.len();
+        if data_len > self.max_instruction_data {
+            self.stats.oversized.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Skipping instruction {} of {}: {} bytes of data exceeds the {} byte limit",
+                nested_instruction.metadata.instruction_path(),
+                transaction_signature,
+                data_len,
+                self.max_instruction_data
+            );
+            return Ok(None);
+        }
+        let decoded = match panic::catch_unwind(AssertUnwindSafe(|| {
+            decoder.decode_instruction(&nested_instruction.instruction)
+        })) {
+            Ok(decoded) => decoded,
+            Err(payload) => {
+                self.stats.decoder_panics.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Decoder panicked on instruction {} of {}: {}",
+                    nested_instruction.metadata.instruction_path(),
+                    transaction_signature,
+                    panic_message(payload.as_ref())
+                );
+                return Ok(None);
+            }
+        };
+        if decoded.is_none() && self.emits(&nested_instruction.instruction.data) {
+            return Err(ProcessError::DecodeFailed {
+                signature: transaction_signature.to_string(),
+                instruction_path: nested_instruction.metadata.instruction_path(),
+            });
+        }
+        Ok(decoded)
+    }
+
+    fn dump_undecoded(
+        &self,
+        nested_instruction: &NestedInstruction,
+        transaction_signature: &str,
+        transaction_slot: u64,
+    ) -> Result<(), ProcessError> {
+        let instruction = &nested_instruction.instruction;
+        if self.dump_undecoded.map(|id| id.to_bytes()) != Some(instruction.program_id.to_bytes())
+            || self.warming_up(transaction_slot)
+        {
+            return Ok(());
+        }
+        let undecoded = UndecodedInstruction {
+            signature: transaction_signature.to_string(),
+            slot: transaction_slot,
+            instruction_path: nested_instruction.metadata.instruction_path(),
+            stack_height: nested_instruction.metadata.stack_height,
+            program_id: instruction.program_id.to_string(),
+            data: instruction
+                .data
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account| account.pubkey.to_string())
+                .collect(),
+        };
+        self.write_sinks("undecoded instruction", &undecoded.signature, |sink| {
+            sink.write_undecoded(&undecoded)
+        })
+    }
 
-impl PumpfunTrackingPlugin {
-    /// Creates a new PumpfunTrackingPlugin for the specified mint address
-    pub fn new(mint: Pubkey) -> Self {
-        Self {
+    /// Emits a [`NewHolderEvent`] if `nested_instruction` creates an
+    /// associated token account of the tracked mint for a new owner.
+    ///
+    /// `Create` and `CreateIdempotent` share the same accounts (payer, token
+    /// account, owner, mint, ...). Whether the owner is new is read from the
+    /// token balances; without them only a plain `Create` counts, since an
+    /// idempotent create may have found the account already there.
+    fn detect_new_holder(
+        &self,
+        nested_instruction: &NestedInstruction,
+        transaction_signature: &str,
+        transaction_slot: u64,
+    ) -> Result<(), ProcessError> {
+        let instruction = &nested_instruction.instruction;
+        if instruction.program_id != ASSOCIATED_TOKEN_PROGRAM_ID {
+            return Ok(());
+        }
+        let idempotent = match instruction.data.first() {
+            None | Some(0) => false,
+            Some(1) => true,
+            Some(_) => return Ok(()),
+        };
+        let (Some(token_account), Some(owner), Some(mint)) = (
+            instruction.accounts.get(1),
+            instruction.accounts.get(2),
+            instruction.accounts.get(3),
+        ) else {
+            return Ok(());
+        };
+        if mint.pubkey.to_bytes() != self.mint.to_bytes() {
+            return Ok(());
+        }
+        let mint = mint.pubkey.to_string();
+        let owner = owner.pubkey.to_string();
+        let meta = &nested_instruction.metadata.transaction_metadata.meta;
+        if !gained_token_account(meta, &mint, &owner).unwrap_or(!idempotent) {
+            return Ok(());
+        }
+        let mut event = NewHolderEvent {
+            signature: transaction_signature.to_string(),
+            slot: transaction_slot,
+            instruction_path: nested_instruction.metadata.instruction_path(),
+            owner,
             mint,
-            processor: std::sync::Arc::new(|_evt: &TradeEvent| {}),
+            token_account: token_account.pubkey.to_string(),
+        };
+        self.stats.matches.fetch_add(1, Ordering::Relaxed);
+        if !self.should_emit(event.slot, &event.signature, &event.instruction_path) {
+            return Ok(());
         }
+        if let Some(hasher) = &self.address_hasher {
+            event.owner = hasher.hash(&event.owner);
+            event.token_account = hasher.hash(&event.token_account);
+        }
+        self.write_sinks("new holder event", &event.signature, |sink| {
+            sink.write_new_holder(&event)
+        })?;
+        self.record_emitted(event.slot, &event.signature, &event.instruction_path)
     }
 
-    /// Creates a new PumpfunTrackingPlugin with a custom event processor
-    pub fn with_processor(mint: Pubkey, processor: TradeEventProcessor) -> Self {
-        Self { mint, processor }
+    /// Emits a [`TransferEvent`] if `nested_instruction` is a token transfer
+    /// of the tracked mint.
+    ///
+    /// `TransferChecked` (tag 12) names the mint as its second account. A
+    /// plain `Transfer` (tag 3) does not, so its mint is read from the
+    /// source account's token balance; without balances it cannot be
+    /// attributed and is skipped.
+    fn detect_transfer(
+        &self,
+        nested_instruction: &NestedInstruction,
+        transaction_signature: &str,
+        transaction_slot: u64,
+    ) -> Result<(), ProcessError> {
+        let instruction = &nested_instruction.instruction;
+        if instruction.program_id != TOKEN_PROGRAM_ID
+            && instruction.program_id != TOKEN_2022_PROGRAM_ID
+        {
+            return Ok(());
+        }
+        let (source, mint, destination, authority) = match instruction.data.first() {
+            Some(3) => (
+                instruction.accounts.first(),
+                None,
+                instruction.accounts.get(1),
+                instruction.accounts.get(2),
+            ),
+            Some(12) => (
+                instruction.accounts.first(),
+                instruction.accounts.get(1),
+                instruction.accounts.get(2),
+                instruction.accounts.get(3),
+            ),
+            _ => return Ok(()),
+        };
+        let (Some(source), Some(destination), Some(authority), Some(amount)) = (
+            source,
+            destination,
+            authority,
+            instruction
+                .data
+                .get(1..9)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8 byte slice"))),
+        ) else {
+            return Ok(());
+        };
+        let transaction_metadata = &nested_instruction.metadata.transaction_metadata;
+        let meta = &transaction_metadata.meta;
+        let loaded = &meta.loaded_addresses;
+        let balance = |account: &PubkeyCarbon| {
+            transaction_metadata
+                .message
+                .static_account_keys()
+                .iter()
+                .chain(&loaded.writable)
+                .chain(&loaded.readonly)
+                .position(|key| key.to_bytes() == account.to_bytes())
+                .and_then(|index| token_account_balance(meta, index))
+        };
+        let source_balance = balance(&source.pubkey);
+        let destination_balance = balance(&destination.pubkey);
+        let mint = match mint {
+            Some(mint) => mint.pubkey.to_string(),
+            None => match source_balance {
+                Some(balance) => balance.mint.clone(),
+                None => return Ok(()),
+            },
+        };
+        if mint != self.mint.to_string() {
+            return Ok(());
+        }
+        let owner = |balance: Option<&TransactionTokenBalance>| {
+            balance
+                .map(|balance| balance.owner.clone())
+                .filter(|owner| !owner.is_empty())
+        };
+        let mut event = TransferEvent {
+            signature: transaction_signature.to_string(),
+            slot: transaction_slot,
+            instruction_path: nested_instruction.metadata.instruction_path(),
+            mint,
+            source: source.pubkey.to_string(),
+            destination: destination.pubkey.to_string(),
+            source_owner: owner(source_balance),
+            destination_owner: owner(destination_balance),
+            authority: authority.pubkey.to_string(),
+            amount,
+        };
+        self.stats.matches.fetch_add(1, Ordering::Relaxed);
+        if !self.should_emit(event.slot, &event.signature, &event.instruction_path) {
+            return Ok(());
+        }
+        if let Some(hasher) = &self.address_hasher {
+            event.source = hasher.hash(&event.source);
+            event.destination = hasher.hash(&event.destination);
+            event.source_owner = event.source_owner.map(|owner| hasher.hash(&owner));
+            event.destination_owner = event.destination_owner.map(|owner| hasher.hash(&owner));
+            event.authority = hasher.hash(&event.authority);
+        }
+        self.write_sinks("transfer event", &event.signature, |sink| {
+            sink.write_transfer(&event)
+        })?;
+        self.record_emitted(event.slot, &event.signature, &event.instruction_path)
     }
 
-    /// Recursively processes nested instructions to find and decode trade events
-    fn process(
+    /// Walks `nested_instruction` and its inner instructions once, running
+    /// every enabled detector (decoded events, new holders, transfers and
+    /// undecoded dumps) on each instruction as it is visited. The tree is
+    /// extracted once per transaction in [`Self::handle_transaction`], so
+    /// enabling more event kinds adds work per instruction but never another
+    /// pass over the transaction.
+    ///
+    /// `ancestors` holds the instructions from the top-level one down to the
+    /// parent of `nested_instruction`, and is left as it was on return.
+    fn process<'a>(
         &self,
-        nested_instruction: &NestedInstruction,
+        nested_instruction: &'a NestedInstruction,
+        ancestors: &mut Vec<&'a NestedInstruction>,
         transaction_signature: &str,
         transaction_slot: u64,
-        decoder: &PumpfunDecoder,
-    ) {
+        decoder: &PumpfunInstructionDecoder,
+    ) -> Result<(), ProcessError> {
         // Try to decode the current instruction
-        if let Some(decoded) = decoder.decode_instruction(&nested_instruction.instruction) {
+        let allowed = self.stack_height_allowed(nested_instruction.metadata.stack_height);
+        let decoded = if allowed && self.may_decode(&nested_instruction.instruction.data) {
+            self.decode(nested_instruction, transaction_signature, decoder)?
+        } else {
+            None
+        };
+        if allowed && decoded.is_none() {
+            self.dump_undecoded(nested_instruction, transaction_signature, transaction_slot)?;
+        }
+        if allowed && self.track_holders {
+            self.detect_new_holder(nested_instruction, transaction_signature, transaction_slot)?;
+        }
+        if allowed && self.track_transfers {
+            self.detect_transfer(nested_instruction, transaction_signature, transaction_slot)?;
+        }
+        if let Some(decoded) = decoded {
+            let mut direction_mismatch = None;
+            if let PumpfunInstruction::TradeEvent(te) = &decoded.data {
+                self.record_breadth(te, transaction_slot);
+                self.record_volume(te);
+                if let Some(checker) = &self.direction_checker {
+                    direction_mismatch = checker.check(te, transaction_signature);
+                }
+            }
             match decoded.data {
+                PumpfunInstruction::CreateEvent(ce) => {
+                    if let Some(creators) = &self.creators {
+                        creators
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .insert(ce.mint.to_string(), ce.creator.to_string());
+                    }
+                }
+                PumpfunInstruction::TradeEvent(te)
+                    if !self.tracks(&te)
+                        || !self.creator_allowed(&te)
+                        || !self.curve_progress_allowed(&te) => {}
                 PumpfunInstruction::TradeEvent(te) => {
+                    if let Some(verifier) = &self.balance_verifier {
+                        verifier.check(
+                            &nested_instruction.metadata.transaction_metadata.meta,
+                            &te,
+                            transaction_signature,
+                        );
+                    }
+
                     let (amount_in, amount_out) = if te.is_buy {
                         (te.sol_amount, te.token_amount)
                     } else {
@@ -82,30 +2930,101 @@ impl PumpfunTrackingPlugin {
                         metadata: &nested_instruction.metadata,
                         signature: transaction_signature.to_string(),
                         slot: transaction_slot,
+                        instruction_path: nested_instruction.metadata.instruction_path(),
                         timestamp: te.timestamp,
                         program_id: nested_instruction.instruction.program_id.to_string(),
+                        program_stack: program_stack(ancestors, nested_instruction),
                         mint: te.mint.to_string(),
                         payer: te.user.to_string(),
                         amount_in,
                         amount_out,
                         is_buy: te.is_buy,
+                        priority_fee_micro_lamports: compute_unit_price(
+                            &nested_instruction.metadata.transaction_metadata.message,
+                        ),
+                        accounts: if self.include_accounts {
+                            // The event is logged through a self-CPI whose only
+                            // account is the event authority; the trade's accounts
+                            // belong to the buy/sell instruction that emitted it.
+                            let source = ancestors.last().copied().unwrap_or(nested_instruction);
+                            source
+                                .instruction
+                                .accounts
+                                .iter()
+                                .map(AccountRef::from)
+                                .collect()
+                        } else {
+                            Vec::new()
+                        },
+                        symbol: self.symbol_for(&te.mint),
+                        ewma_price: self.update_price(&te),
+                        curve_progress: curve_progress(te.virtual_token_reserves),
+                        direction_mismatch,
+                        return_data: return_data(
+                            &nested_instruction.metadata.transaction_metadata.meta,
+                        ),
                     };
+                    self.update_vwap(&te);
+                    self.record_interarrival(&te);
 
                     (self.processor)(&event);
+                    self.payers
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .insert(&event.payer);
+                    self.stats.matches.fetch_add(1, Ordering::Relaxed);
+                    self.dispatch(&event)?;
+                }
+                PumpfunInstruction::CompletePumpAmmMigrationEvent(me)
+                    if self.track_migrations
+                        && (self.breadth.is_none()
+                            || (self.track_mint && me.mint.to_bytes() == self.mint.to_bytes())) =>
+                {
+                    let mut event = MigrationEvent {
+                        signature: transaction_signature.to_string(),
+                        slot: transaction_slot,
+                        instruction_path: nested_instruction.metadata.instruction_path(),
+                        timestamp: me.timestamp,
+                        mint: me.mint.to_string(),
+                        pool: me.pool.to_string(),
+                        bonding_curve: me.bonding_curve.to_string(),
+                        user: me.user.to_string(),
+                        mint_amount: me.mint_amount,
+                        sol_amount: me.sol_amount,
+                        pool_migration_fee: me.pool_migration_fee,
+                    };
+                    self.stats.matches.fetch_add(1, Ordering::Relaxed);
+                    if self.should_emit(event.slot, &event.signature, &event.instruction_path) {
+                        if let Some(hasher) = &self.address_hasher {
+                            event.user = hasher.hash(&event.user);
+                        }
+                        self.write_sinks("migration event", &event.signature, |sink| {
+                            sink.write_migration(&event)
+                        })?;
+                        self.record_emitted(event.slot, &event.signature, &event.instruction_path)?;
+                    }
                 }
                 _ => {}
             }
         }
 
         // Recursively process all inner instructions
-        for inner_instruction in nested_instruction.inner_instructions.iter() {
-            self.process(
-                inner_instruction,
-                transaction_signature,
-                transaction_slot,
-                decoder,
-            );
-        }
+        ancestors.push(nested_instruction);
+        let result =
+            nested_instruction
+                .inner_instructions
+                .iter()
+                .try_for_each(|inner_instruction| {
+                    self.process(
+                        inner_instruction,
+                        ancestors,
+                        transaction_signature,
+                        transaction_slot,
+                        decoder,
+                    )
+                });
+        ancestors.pop();
+        result
     }
 }
 
@@ -122,57 +3041,56 @@ impl Plugin for PumpfunTrackingPlugin {
         _db: Option<Arc<Client>>,
         transaction: &'a TransactionData,
     ) -> PluginFuture<'a> {
-        let mint = self.mint;
         async move {
-            let message = &transaction.transaction.message;
-            let (account_keys, instructions) = match message {
-                VersionedMessage::Legacy(msg) => (&msg.account_keys, &msg.instructions),
-                VersionedMessage::V0(msg) => (&msg.account_keys, &msg.instructions),
-            };
-
-            if instructions.is_empty() {
+            if self.is_paused() {
+                self.stats
+                    .paused_transactions
+                    .fetch_add(1, Ordering::Relaxed);
                 return Ok(());
             }
-
-            // Check if the mint address is involved in any instruction
-            let mint_involved = account_keys.iter().any(|&key| key == mint);
-
-            if mint_involved {
-                info!("Mint involved in transaction: {:?}", transaction.signature);
-
-                // Create TransactionMetadata from transaction data
-                let transaction_metadata = Arc::new(TransactionMetadata {
-                    slot: transaction.slot,
-                    signature: transaction.signature,
-                    fee_payer: transaction.transaction.message.static_account_keys()[0],
-                    meta: transaction.transaction_status_meta.clone(),
-                    message: transaction.transaction.message.clone(),
-                });
-
-                // Extract instructions with metadata using the transformers module
-                let instructions_with_metadata: InstructionsWithMetadata =
-                    extract_instructions_with_metadata(
-                        &transaction_metadata,
-                        &transaction.transaction.message,
-                        &transaction.transaction_status_meta,
-                    );
-
-                let nested_instructions: NestedInstructions = instructions_with_metadata.into();
-
-                // Process each instruction recursively
-                let decoder = PumpfunDecoder;
-                let signature_str = transaction.signature.to_string();
-
-                for nested_instruction in nested_instructions.iter() {
-                    self.process(
-                        nested_instruction,
-                        &signature_str,
-                        transaction.slot,
-                        &decoder,
-                    );
+            if let Some((signature, path)) = &self.export {
+                if transaction.signature == *signature {
+                    let bundle = TransactionBundle::from_transaction(transaction)?;
+                    let mut file = BufWriter::new(File::create(path)?);
+                    bundle.write_to(&mut file)?;
+                    file.flush()?;
+                    info!("Exported transaction {} to {}", signature, path.display());
                 }
             }
-
+            if let Some(tracker) = &self.slot_tracker {
+                if tracker.on_transaction(transaction.slot) {
+                    return Ok(());
+                }
+            }
+            #[cfg(feature = "metadata")]
+            if let Some(metadata) = &self.metadata {
+                if self.track_mint && self.matches(transaction) {
+                    metadata.ensure(&self.mint).await;
+                }
+            }
+            if !self.skip_errors {
+                return Ok(self.handle_transaction(transaction)?);
+            }
+            let outcome =
+                panic::catch_unwind(AssertUnwindSafe(|| self.handle_transaction(transaction)));
+            let (kind, reason) = match outcome {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(err)) => (SkipKind::from(&err), err.to_string()),
+                Err(payload) => (SkipKind::Panicked, panic_message(payload.as_ref())),
+            };
+            warn!(
+                "Skipping transaction {} in slot {}: {}",
+                transaction.signature, transaction.slot, reason
+            );
+            self.skipped
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(SkippedTransaction {
+                    slot: transaction.slot,
+                    signature: transaction.signature.to_string(),
+                    kind,
+                    reason,
+                });
             Ok(())
         }
         .boxed()
@@ -183,15 +3101,88 @@ impl Plugin for PumpfunTrackingPlugin {
         &self,
         _thread_id: usize,
         _db: Option<Arc<Client>>,
-        _block: &BlockData,
+        block: &BlockData,
     ) -> PluginFuture<'_> {
+        if let Some(tracker) = &self.slot_tracker {
+            if tracker.on_block(block.slot()) {
+                return async move { Ok(()) }.boxed();
+            }
+        }
+        self.stats
+            .last_slot
+            .fetch_max(block.slot(), Ordering::Relaxed);
+        if let Some(breadth) = &self.breadth {
+            let closed = breadth
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .on_slot(block.slot());
+            for snapshot in &closed {
+                self.write_breadth(snapshot);
+            }
+        }
+        let block_time = match block {
+            BlockData::Block { block_time, .. } => *block_time,
+            BlockData::LeaderSkipped { .. } => None,
+        };
+        if let Err(err) = self.write_block_summary(block.slot(), block_time) {
+            if !self.skip_errors {
+                return async move { Err(err.into()) }.boxed();
+            }
+        }
+        if let Some(pending) = &self.pending_by_slot {
+            let mut trades = pending
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(&block.slot())
+                .unwrap_or_default();
+            self.mark_sandwiches(&mut trades);
+            let mut result = Ok(());
+            for (mut trade, processed_at) in trades {
+                trade.block_time = block_time;
+                trade.processing_latency_ms =
+                    block_time.map(|block_time| processed_at - block_time * 1000);
+                if let Some(latency) = trade.processing_latency_ms {
+                    self.latencies
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .record(latency);
+                }
+                let emitted = self.emit(trade);
+                if result.is_ok() && !self.skip_errors {
+                    result = emitted;
+                }
+            }
+            if let Err(err) = result {
+                return async move { Err(err.into()) }.boxed();
+            }
+        }
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.tick(&self.stats);
+        }
+        if let Some(status_file) = &self.status_file {
+            status_file.tick(&self.stats);
+        }
         async move { Ok(()) }.boxed()
     }
 
     #[inline(always)]
     fn on_load(&self, _db: Option<Arc<Client>>) -> PluginFuture<'_> {
         let mint = self.mint;
+        if let Some(status_file) = &self.status_file {
+            status_file.tick(&self.stats);
+        }
+        let started = if self.first_segment {
+            self.write_control(&ControlEvent::RunStarted {
+                schema_version: SCHEMA_VERSION,
+                mint: mint.to_string(),
+                start_slot: self.slot_range.as_ref().map(|range| range.start),
+                end_slot: self.slot_range.as_ref().map(|range| range.end),
+            })
+        } else {
+            Ok(())
+        };
         async move {
+            started?;
             info!("Pumpfun Tracking Plugin loaded for mint: {}", mint);
             Ok(())
         }
@@ -200,6 +3191,847 @@ impl Plugin for PumpfunTrackingPlugin {
 
     #[inline(always)]
     fn on_exit(&self, _db: Option<Arc<Client>>) -> PluginFuture<'_> {
-        async move { Ok(()) }.boxed()
+        async move {
+            if self.last_segment {
+                self.log_run_report();
+            }
+            let mut pending_result = Ok(());
+            if let Some(summaries) = &self.block_summaries {
+                // Blocks that never arrived, for example at the end of a range.
+                let slots: Vec<u64> = summaries
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .keys()
+                    .copied()
+                    .collect();
+                for slot in slots {
+                    let written = self.write_block_summary(slot, None);
+                    if pending_result.is_ok() && !self.skip_errors {
+                        pending_result = written;
+                    }
+                }
+            }
+            if let Some(pending) = &self.pending_by_slot {
+                let pending = std::mem::take(
+                    &mut *pending
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()),
+                );
+                for mut trades in pending.into_values() {
+                    self.mark_sandwiches(&mut trades);
+                    for (trade, _) in trades {
+                        let emitted = self.emit(trade);
+                        if pending_result.is_ok() && !self.skip_errors {
+                            pending_result = emitted;
+                        }
+                    }
+                }
+            }
+            if let Some(vwap) = &self.vwap {
+                let mut vwap = vwap.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                for snapshot in vwap.drain() {
+                    self.write_vwap(&snapshot);
+                }
+                if vwap.late() > 0 {
+                    warn!(
+                        "{} trade(s) arrived after their VWAP window closed and were left out",
+                        vwap.late()
+                    );
+                }
+            }
+            if let Some(breadth) = &self.breadth {
+                let mut breadth = breadth.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                for snapshot in breadth.drain() {
+                    self.write_breadth(&snapshot);
+                }
+                if breadth.late() > 0 {
+                    warn!(
+                        "{} trade(s) arrived after their breadth window closed and were left out",
+                        breadth.late()
+                    );
+                }
+            }
+            if self.last_segment {
+                let finished = self.write_control(&ControlEvent::RunFinished {
+                    mint: self.mint.to_string(),
+                    start_slot: self.slot_range.as_ref().map(|range| range.start),
+                    end_slot: self.slot_range.as_ref().map(|range| range.end),
+                    last_slot: self.stats.last_slot.load(Ordering::Relaxed),
+                    events: self.stats.matches.load(Ordering::Relaxed),
+                });
+                if pending_result.is_ok() {
+                    pending_result = finished;
+                }
+            }
+            self.flush()?;
+            if let Some(status_file) = &self.status_file {
+                status_file.write(&self.stats);
+            }
+            pending_result?;
+            if !self.last_segment {
+                return Ok(());
+            }
+            // Logged first: a run failing the strict mint check is one whose
+            // recovered errors are most worth seeing.
+            self.error_report().log();
+            if self.strict_mint && !self.mint_seen() {
+                return Err(format!(
+                    "mint {} was never seen in the processed range; check the mint address and slot range",
+                    self.mint
+                )
+                .into());
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_message::VersionedMessage;
+
+    /// Events written to a sink by the streaming path for `transaction`.
+    fn streamed(
+        plugin: &PumpfunTrackingPlugin,
+        transaction: &TransactionData,
+    ) -> Vec<OwnedTradeEvent> {
+        let sink = Arc::new(CollectSink::default());
+        plugin
+            .clone()
+            .with_sink(sink.clone())
+            .handle_transaction(transaction)
+            .unwrap();
+        let events = std::mem::take(&mut *sink.events.lock().unwrap());
+        events
+    }
+
+    /// Clears the ingest time, which differs between two passes.
+    fn without_ingest_time(mut events: Vec<OwnedTradeEvent>) -> Vec<OwnedTradeEvent> {
+        for event in &mut events {
+            event.ingest_time = None;
+        }
+        events
+    }
+
+    fn net_payers(plugin: &PumpfunTrackingPlugin) -> usize {
+        plugin
+            .net_positions
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .payers
+            .len()
+    }
+
+    #[test]
+    fn process_transaction_matches_streaming_path() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_transform(Arc::new(|event| event.symbol = Some("TEST".to_string())));
+        for fixture in fixtures() {
+            let returned = plugin.process_transaction(&fixture.transaction).unwrap();
+            assert_eq!(returned.len(), 1, "fixture {}", fixture.name);
+            assert_eq!(returned[0].symbol.as_deref(), Some("TEST"));
+            assert_eq!(
+                without_ingest_time(returned),
+                without_ingest_time(streamed(&plugin, &fixture.transaction)),
+                "fixture {}",
+                fixture.name
+            );
+        }
+    }
+
+    /// Counts its calls and delegates to [`PumpfunDecoder`], or decodes
+    /// nothing if `reject`.
+    struct CountingDecoder {
+        calls: AtomicU64,
+        reject: bool,
+    }
+
+    impl<'a> InstructionDecoder<'a> for CountingDecoder {
+        type InstructionType = PumpfunInstruction;
+
+        fn decode_instruction(
+            &self,
+            instruction: &'a solana_instruction::Instruction,
+        ) -> Option<DecodedInstruction<PumpfunInstruction>> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if self.reject {
+                return None;
+            }
+            PumpfunDecoder.decode_instruction(instruction)
+        }
+    }
+
+    #[test]
+    fn process_transaction_uses_the_custom_decoder() {
+        let decoder = Arc::new(CountingDecoder {
+            calls: AtomicU64::new(0),
+            reject: false,
+        });
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT).with_decoder(decoder.clone());
+        let default = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        for fixture in fixtures() {
+            assert_eq!(
+                without_ingest_time(plugin.process_transaction(&fixture.transaction).unwrap()),
+                without_ingest_time(default.process_transaction(&fixture.transaction).unwrap()),
+                "fixture {}",
+                fixture.name
+            );
+        }
+        // Only the trade events carry a discriminator the plugin emits.
+        assert_eq!(
+            decoder.calls.load(Ordering::Relaxed),
+            fixtures().len() as u64
+        );
+
+        let rejecting =
+            PumpfunTrackingPlugin::new(FIXTURE_MINT).with_decoder(Arc::new(CountingDecoder {
+                calls: AtomicU64::new(0),
+                reject: true,
+            }));
+        assert!(matches!(
+            rejecting.process_transaction(&fixtures()[0].transaction),
+            Err(ProcessError::DecodeFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn process_transaction_collects_before_diversion_without_accumulating() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_sink(Arc::new(CollectSink::default()))
+            .with_net_positions(true, None);
+        for fixture in fixtures() {
+            assert_eq!(
+                plugin
+                    .process_transaction(&fixture.transaction)
+                    .unwrap()
+                    .len(),
+                1
+            );
+        }
+        assert_eq!(net_payers(&plugin), 0);
+        assert_eq!(plugin.stats.matches.load(Ordering::Relaxed), 0);
+        assert!(!plugin.mint_seen());
+    }
+
+    #[test]
+    fn diversion_accumulates_without_sinks() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT).with_net_positions(true, None);
+        plugin
+            .handle_transaction(&fixtures()[0].transaction)
+            .unwrap();
+        assert_eq!(net_payers(&plugin), 1);
+    }
+
+    #[test]
+    fn matches_transactions_referencing_the_mint() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let other = PumpfunTrackingPlugin::new(Pubkey::new_unique());
+        for fixture in fixtures() {
+            assert!(
+                plugin.matches(&fixture.transaction),
+                "fixture {}",
+                fixture.name
+            );
+            assert!(
+                !other.matches(&fixture.transaction),
+                "fixture {}",
+                fixture.name
+            );
+        }
+        let mut empty = fixtures().remove(0).transaction;
+        match &mut empty.transaction.message {
+            VersionedMessage::Legacy(message) => message.instructions.clear(),
+            VersionedMessage::V0(message) => message.instructions.clear(),
+        }
+        assert!(!plugin.matches(&empty));
+    }
+
+    #[test]
+    fn filters_compose_in_order_and_short_circuit() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let counted = calls.clone();
+        let transaction = &fixtures()[0].transaction;
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_filter(|transaction: &TransactionData| transaction.slot == 0)
+            .with_filter(move |_: &TransactionData| {
+                counted.fetch_add(1, Ordering::Relaxed);
+                true
+            });
+        assert!(plugin.matches(transaction));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        let mut later = transaction.clone();
+        later.slot = 1;
+        assert!(!plugin.matches(&later));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        let rejecting = plugin.with_filter(|_: &TransactionData| false);
+        assert!(!rejecting.matches(transaction));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn transforms_redact_the_payer_in_registration_order() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_transform(Arc::new(|event| event.payer = "redacted".to_string()))
+            .with_transform(Arc::new(|event| event.payer.push_str("-twice")));
+        for fixture in fixtures() {
+            let events = streamed(&plugin, &fixture.transaction);
+            assert_eq!(events.len(), 1, "fixture {}", fixture.name);
+            assert_eq!(events[0].payer, "redacted-twice");
+        }
+    }
+
+    #[test]
+    fn stack_height_range_bounds_are_inclusive() {
+        // The fixtures' trade events are logged at stack height 2.
+        let cases = [
+            (None, None, 1),
+            (Some(2), Some(2), 1),
+            (Some(1), Some(2), 1),
+            (None, Some(1), 0),
+            (Some(3), None, 0),
+        ];
+        for (min, max, expected) in cases {
+            let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT).with_stack_height_range(min, max);
+            for fixture in fixtures() {
+                assert_eq!(
+                    streamed(&plugin, &fixture.transaction).len(),
+                    expected,
+                    "{min:?}..={max:?}, fixture {}",
+                    fixture.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn skips_oversized_instructions() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let mut transaction = fixtures().remove(0).transaction;
+        let inner = &mut transaction
+            .transaction_status_meta
+            .inner_instructions
+            .as_mut()
+            .unwrap()[0]
+            .instructions[0]
+            .instruction
+            .data;
+        inner.resize(DEFAULT_MAX_INSTRUCTION_DATA + 1, 0);
+        assert!(streamed(&plugin, &transaction).is_empty());
+        assert_eq!(plugin.stats.oversized.load(Ordering::Relaxed), 1);
+
+        let raised = plugin.with_max_instruction_data(DEFAULT_MAX_INSTRUCTION_DATA + 1);
+        assert_eq!(streamed(&raised, &transaction).len(), 1);
+        assert_eq!(raised.stats.oversized.load(Ordering::Relaxed), 1);
+    }
+
+    /// Sink whose writes always fail.
+    struct FailingSink;
+
+    impl TradeSink for FailingSink {
+        fn write_event(&self, _: &OwnedTradeEvent) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trade_event_data(transaction: &mut TransactionData) -> &mut Vec<u8> {
+        &mut transaction
+            .transaction_status_meta
+            .inner_instructions
+            .as_mut()
+            .unwrap()[0]
+            .instructions[0]
+            .instruction
+            .data
+    }
+
+    #[test]
+    fn process_errors_name_the_failure() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let fixture = fixtures().remove(0);
+        let signature = fixture.transaction.signature.to_string();
+
+        let mut truncated = fixture.transaction.clone();
+        trade_event_data(&mut truncated).truncate(16 + 32);
+        assert!(matches!(
+            plugin.process_transaction(&truncated),
+            Err(ProcessError::DecodeFailed { signature: s, .. }) if s == signature
+        ));
+
+        let mut dangling = fixture.transaction.clone();
+        dangling
+            .transaction_status_meta
+            .inner_instructions
+            .as_mut()
+            .unwrap()[0]
+            .index = 1;
+        assert!(matches!(
+            plugin.process_transaction(&dangling),
+            Err(ProcessError::MetadataInvalid { signature: s, .. }) if s == signature
+        ));
+
+        let failing = plugin.clone().with_sink(Arc::new(FailingSink));
+        assert!(matches!(
+            failing.handle_transaction(&fixture.transaction),
+            Err(ProcessError::SinkFailed { signature: s, event: "trade event", reason })
+                if s == signature && reason == "disk full"
+        ));
+    }
+
+    #[test]
+    fn skip_errors_records_the_failure_instead() {
+        let mut truncated = fixtures().remove(0).transaction;
+        trade_event_data(&mut truncated).truncate(16 + 32);
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        assert!(futures::executor::block_on(plugin.on_transaction(0, None, &truncated)).is_err());
+        assert!(plugin.skipped().is_empty());
+
+        let skipping = plugin.with_skip_errors(true);
+        futures::executor::block_on(skipping.on_transaction(0, None, &truncated)).unwrap();
+        let skipped = skipping.skipped();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].kind, SkipKind::DecodeFailed);
+    }
+
+    #[test]
+    fn hash_payers_replaces_the_payer_with_its_hash() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT).with_hash_payers("secret");
+        for fixture in fixtures() {
+            let payer = fixture
+                .transaction
+                .transaction
+                .message
+                .static_account_keys()[0];
+            let events = streamed(&plugin, &fixture.transaction);
+            assert_eq!(events.len(), 1, "fixture {}", fixture.name);
+            assert_eq!(
+                events[0].payer,
+                AddressHasher::new("secret").hash(&payer.to_string())
+            );
+            assert_eq!(events[0].mint, FIXTURE_MINT.to_string());
+        }
+    }
+
+    #[test]
+    fn breadth_without_a_mint_matches_on_the_program_alone() {
+        let plugin = PumpfunTrackingPlugin::new(Pubkey::default())
+            .with_breadth_window(150)
+            .with_track_mint(false);
+        for fixture in fixtures() {
+            assert!(
+                plugin.matches(&fixture.transaction),
+                "fixture {}",
+                fixture.name
+            );
+            assert!(streamed(&plugin, &fixture.transaction).is_empty());
+        }
+        assert!(!plugin.mint_seen());
+
+        // A transaction that references the placeholder key, here the system
+        // program, but not pumpfun is not matched.
+        let mut system = fixtures().remove(0).transaction;
+        match &mut system.transaction.message {
+            VersionedMessage::Legacy(message) => message.account_keys[3] = Pubkey::default(),
+            VersionedMessage::V0(message) => message.account_keys[3] = Pubkey::default(),
+        }
+        assert!(!plugin.matches(&system));
+    }
+
+    #[test]
+    fn dedup_store_suppresses_on_resume_only_what_every_sink_wrote() {
+        let path = std::env::temp_dir().join(format!("pulstream-dedup-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let transaction = &fixtures()[0].transaction;
+
+        // A failed write is not recorded, so the resumed run emits it again.
+        let failing = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_dedup_store(DedupStore::open(&path, 0).unwrap())
+            .with_sink(Arc::new(FailingSink));
+        assert!(failing.handle_transaction(transaction).is_err());
+        failing.flush().unwrap();
+        drop(failing);
+
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_dedup_store(DedupStore::open(&path, 0).unwrap());
+        assert_eq!(streamed(&plugin, transaction).len(), 1);
+        // The entry reaches disk only once the sinks are flushed.
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        plugin.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        drop(plugin);
+
+        let resumed = PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_dedup_store(DedupStore::open(&path, 0).unwrap());
+        assert!(streamed(&resumed, transaction).is_empty());
+        assert_eq!(streamed(&resumed, &fixtures()[1].transaction).len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn turning_off_block_time_keeps_holding_for_other_modes() {
+        let held = |plugin: &PumpfunTrackingPlugin| plugin.pending_by_slot.is_some();
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        assert!(!held(&plugin));
+        assert!(held(
+            &plugin
+                .clone()
+                .with_detect_sandwiches(true)
+                .with_block_time(false)
+        ));
+        assert!(held(
+            &plugin
+                .clone()
+                .with_timestamp_source(TimestampSource::Block)
+                .with_block_time(false)
+        ));
+        assert!(!held(
+            &plugin
+                .clone()
+                .with_block_time(true)
+                .with_detect_sandwiches(true)
+                .with_block_time(false)
+                .with_detect_sandwiches(false)
+        ));
+    }
+
+    /// Future that is pending for `polls` polls, letting the others in a
+    /// `join_all` start before it finishes.
+    #[cfg(feature = "metadata")]
+    struct Yield {
+        polls: usize,
+    }
+
+    #[cfg(feature = "metadata")]
+    impl std::future::Future for Yield {
+        type Output = ();
+
+        fn poll(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.polls == 0 {
+                return std::task::Poll::Ready(());
+            }
+            self.polls -= 1;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }
+
+    /// An enricher whose lookups count themselves and the most in flight.
+    #[cfg(feature = "metadata")]
+    fn counting_enricher(concurrency: usize) -> (MetadataEnricher, Arc<[AtomicU64; 3]>) {
+        // Calls, lookups in flight and the most in flight at once.
+        let counts: Arc<[AtomicU64; 3]> = Arc::default();
+        let counted = counts.clone();
+        let lookup: MetadataLookup = Arc::new(move |_| {
+            let counts = counted.clone();
+            async move {
+                counts[0].fetch_add(1, Ordering::SeqCst);
+                let in_flight = counts[1].fetch_add(1, Ordering::SeqCst) + 1;
+                counts[2].fetch_max(in_flight, Ordering::SeqCst);
+                Yield { polls: 3 }.await;
+                counts[1].fetch_sub(1, Ordering::SeqCst);
+                Ok(Some(TokenMetadata {
+                    name: "Test".to_string(),
+                    symbol: "TEST".to_string(),
+                }))
+            }
+            .boxed()
+        });
+        (MetadataEnricher::with_lookup(lookup, concurrency), counts)
+    }
+
+    #[cfg(feature = "metadata")]
+    #[test]
+    fn metadata_lookups_are_bounded_and_coalesced() {
+        let (enricher, counts) = counting_enricher(2);
+        let mints: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        futures::executor::block_on(futures::future::join_all(
+            mints.iter().map(|mint| enricher.ensure(mint)),
+        ));
+        assert_eq!(counts[0].load(Ordering::SeqCst), 5);
+        assert_eq!(counts[2].load(Ordering::SeqCst), 2);
+
+        let (enricher, counts) = counting_enricher(2);
+        let mint = Pubkey::new_unique();
+        futures::executor::block_on(futures::future::join_all(
+            (0..4).map(|_| enricher.ensure(&mint)),
+        ));
+        assert_eq!(counts[0].load(Ordering::SeqCst), 1);
+        assert_eq!(
+            enricher
+                .cached(&mint.to_string())
+                .flatten()
+                .map(|metadata| metadata.symbol),
+            Some("TEST".to_string())
+        );
+    }
+
+    #[test]
+    fn program_stack_runs_from_the_router_to_pumpfun() {
+        let router = Pubkey::new_unique();
+        let direct = fixtures().remove(0).transaction;
+        let mut routed = direct.clone();
+        let VersionedMessage::Legacy(message) = &mut routed.transaction.message else {
+            unreachable!("fixtures are legacy transactions");
+        };
+        // router -> pumpfun buy -> pumpfun self-CPI logging the trade event.
+        message.account_keys.push(router);
+        message.header.num_readonly_unsigned_accounts += 1;
+        let buy = std::mem::replace(
+            &mut message.instructions[0],
+            solana_message::compiled_instruction::CompiledInstruction {
+                program_id_index: 4,
+                accounts: vec![0, 1, 3],
+                data: vec![1],
+            },
+        );
+        let inner = &mut routed
+            .transaction_status_meta
+            .inner_instructions
+            .as_mut()
+            .unwrap()[0]
+            .instructions;
+        inner[0].stack_height = Some(3);
+        inner.insert(
+            0,
+            solana_transaction_status::InnerInstruction {
+                instruction: buy,
+                stack_height: Some(2),
+            },
+        );
+
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let pumpfun = PUMPFUN_PROGRAM_ID.to_string();
+        let direct = plugin.process_transaction(&direct).unwrap();
+        assert_eq!(direct[0].program_stack, std::slice::from_ref(&pumpfun));
+        let routed = plugin.process_transaction(&routed).unwrap();
+        assert_eq!(routed.len(), 1);
+        assert_eq!(routed[0].program_stack, [router.to_string(), pumpfun]);
+        assert_eq!(routed[0].instruction_path, "0.0.0");
+    }
+
+    /// A fixture trade whose curve has sold `sold` of the sellable tokens, or
+    /// that carried no reserves if `None`.
+    fn trade_at(sold: Option<u64>) -> TradeEventData {
+        use crate::utils::curve::INITIAL_VIRTUAL_TOKEN_RESERVES;
+        let mut transaction = fixtures().remove(0).transaction;
+        let mut trade =
+            <TradeEventData as CarbonDeserialize>::deserialize(trade_event_data(&mut transaction))
+                .unwrap();
+        trade.virtual_token_reserves = sold.map_or(0, |sold| INITIAL_VIRTUAL_TOKEN_RESERVES - sold);
+        trade
+    }
+
+    #[test]
+    fn curve_progress_bounds_are_inclusive_and_missing_reserves_opt_in() {
+        use crate::utils::curve::INITIAL_REAL_TOKEN_RESERVES as REAL;
+        let (start, quarter, half, three_quarters, end, missing) = (
+            trade_at(Some(0)),
+            trade_at(Some(REAL / 4)),
+            trade_at(Some(REAL / 2)),
+            trade_at(Some(REAL / 4 * 3)),
+            trade_at(Some(REAL)),
+            trade_at(None),
+        );
+        let allowed = |plugin: &PumpfunTrackingPlugin| {
+            [&start, &quarter, &half, &three_quarters, &end, &missing]
+                .map(|trade| plugin.curve_progress_allowed(trade))
+        };
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+
+        assert_eq!(allowed(&plugin), [true; 6]);
+        assert_eq!(
+            allowed(
+                &plugin
+                    .clone()
+                    .with_curve_progress_range(Some(0.25), Some(0.75), false)
+            ),
+            [false, true, true, true, false, false]
+        );
+        assert_eq!(
+            allowed(
+                &plugin
+                    .clone()
+                    .with_curve_progress_range(Some(0.5), None, false)
+            ),
+            [false, false, true, true, true, false]
+        );
+        assert_eq!(
+            allowed(
+                &plugin
+                    .clone()
+                    .with_curve_progress_range(None, Some(0.5), false)
+            ),
+            [true, true, true, false, false, false]
+        );
+        assert_eq!(
+            allowed(
+                &plugin
+                    .clone()
+                    .with_curve_progress_range(Some(0.25), Some(0.75), true)
+            ),
+            [false, true, true, true, false, true]
+        );
+    }
+
+    /// Records the kind and instruction path of every event written.
+    #[derive(Default)]
+    struct KindSink {
+        events: Mutex<Vec<(&'static str, String)>>,
+    }
+
+    impl KindSink {
+        fn push(&self, kind: &'static str, instruction_path: &str) -> io::Result<()> {
+            self.events
+                .lock()
+                .unwrap()
+                .push((kind, instruction_path.to_string()));
+            Ok(())
+        }
+    }
+
+    impl TradeSink for KindSink {
+        fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+            self.push("trade", &event.instruction_path)
+        }
+
+        fn write_new_holder(&self, event: &NewHolderEvent) -> io::Result<()> {
+            self.push("new holder", &event.instruction_path)
+        }
+
+        fn write_transfer(&self, event: &TransferEvent) -> io::Result<()> {
+            self.push("transfer", &event.instruction_path)
+        }
+
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn every_detector_sees_its_instructions_in_the_single_pass() {
+        let mut transaction = fixtures().remove(0).transaction;
+        let VersionedMessage::Legacy(message) = &mut transaction.transaction.message else {
+            unreachable!("fixtures are legacy transactions");
+        };
+        // Keys 4 to 7: ATA program, token program, the trader's token account
+        // and a recipient's.
+        message.account_keys.extend([
+            Pubkey::new_from_array(ASSOCIATED_TOKEN_PROGRAM_ID.to_bytes()),
+            Pubkey::new_from_array(TOKEN_PROGRAM_ID.to_bytes()),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ]);
+        message.header.num_readonly_unsigned_accounts += 2;
+        let create_account = solana_message::compiled_instruction::CompiledInstruction {
+            program_id_index: 4,
+            accounts: vec![0, 6, 0, 1],
+            data: Vec::new(),
+        };
+        let mut transfer_checked = vec![12];
+        transfer_checked.extend_from_slice(&1_000u64.to_le_bytes());
+        transfer_checked.push(6);
+        let transfer = solana_message::compiled_instruction::CompiledInstruction {
+            program_id_index: 5,
+            accounts: vec![6, 1, 7, 0],
+            data: transfer_checked,
+        };
+        message.instructions.insert(0, create_account);
+        message.instructions.push(transfer);
+        transaction
+            .transaction_status_meta
+            .inner_instructions
+            .as_mut()
+            .unwrap()[0]
+            .index = 1;
+
+        let sink = Arc::new(KindSink::default());
+        PumpfunTrackingPlugin::new(FIXTURE_MINT)
+            .with_track_holders(true)
+            .with_track_transfers(true)
+            .with_sink(sink.clone())
+            .handle_transaction(&transaction)
+            .unwrap();
+        let events = sink.events.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [
+                ("new holder", "0".to_string()),
+                ("trade", "1.0".to_string()),
+                ("transfer", "2".to_string()),
+            ]
+        );
+    }
+
+    /// Expected output of a fixture trade through a default plugin, written
+    /// out in full so a decoder bump or transformer edit that changes any
+    /// field fails here. `ingest_time` is the wall clock and left out.
+    fn snapshot(signature: &str, is_buy: bool, amount_in: u64, amount_out: u64) -> OwnedTradeEvent {
+        let pumpfun = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string();
+        OwnedTradeEvent {
+            schema_version: 2,
+            signature: signature.to_string(),
+            slot: 0,
+            instruction_path: "0.0".to_string(),
+            timestamp: 1_735_689_600,
+            program_timestamp: 1_735_689_600,
+            ingest_time: None,
+            program_id: pumpfun.clone(),
+            program_stack: vec![pumpfun],
+            mint: "2zMMhcVQEXDtdE6vsFS7S7D5oUodfJHE8vd1gnBouauv".to_string(),
+            payer: "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
+            amount_in,
+            amount_out,
+            is_buy,
+            priority_fee_micro_lamports: None,
+            accounts: Vec::new(),
+            symbol: None,
+            ewma_price: None,
+            curve_progress: Some(0.0),
+            direction_mismatch: None,
+            block_time: None,
+            return_data: None,
+            processing_latency_ms: None,
+            sandwich_role: None,
+        }
+    }
+
+    #[test]
+    fn fixtures_decode_to_their_snapshots() {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let decoded: Vec<_> = fixtures()
+            .iter()
+            .map(|fixture| {
+                without_ingest_time(plugin.process_transaction(&fixture.transaction).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            [
+                vec![snapshot(
+                    "2AXDGYSE4f2sz7tvMMzyHvUfcoJmxudvdhBcmiUSo6ijwfYmfZYsKRxboQMPh3R4kUhXRVdtSXFXMheka4Rc4P2",
+                    true,
+                    250_000_000,
+                    8_123_456_789_012,
+                )],
+                vec![snapshot(
+                    "3L3RY5sT8K4kyEnqhizwaqxLEbcYvpGrGPNEYRwtbCSUtL6YL86jdrvCbohnP5q8VxQ3qzGmt3W3iQJW97rD7m3",
+                    false,
+                    3_000_000_000_000,
+                    97_500_000,
+                )],
+            ]
+        );
     }
 }
@@ -0,0 +1,219 @@
+use clickhouse::Client;
+use futures_util::future::FutureExt;
+use jetstreamer::{
+    firehose::firehose::{BlockData, EntryData, RewardsData, TransactionData},
+    plugin::{Plugin, PluginFuture},
+};
+use log::info;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Number of power-of-two microsecond buckets; the last one is open-ended.
+const BUCKETS: usize = 32;
+
+/// Lock-free latency histogram with power-of-two microsecond buckets.
+#[derive(Debug)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    total_micros: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        // Bucket `i` holds latencies below 2^i microseconds.
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    /// Upper bound in microseconds of the bucket holding the `q` quantile.
+    fn quantile(&self, q: f64) -> u64 {
+        let count = self.count.load(Ordering::Relaxed);
+        let target = ((count as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, counter) in self.buckets.iter().enumerate() {
+            seen += counter.load(Ordering::Relaxed);
+            if seen >= target {
+                return 1 << bucket;
+            }
+        }
+        1 << (BUCKETS - 1)
+    }
+
+    fn report(&self, plugin: &str, hook: &str) {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return;
+        }
+        let mean = self.total_micros.load(Ordering::Relaxed) / count;
+        info!(
+            "{} {}: {} calls, mean {}µs, p50 <{}µs, p90 <{}µs, p99 <{}µs",
+            plugin,
+            hook,
+            count,
+            mean,
+            self.quantile(0.5),
+            self.quantile(0.9),
+            self.quantile(0.99)
+        );
+        let buckets = self
+            .buckets
+            .iter()
+            .enumerate()
+            .filter_map(|(bucket, counter)| {
+                let n = counter.load(Ordering::Relaxed);
+                (n > 0).then(|| format!("<{}µs: {}", 1u64 << bucket, n))
+            })
+            .collect::<Vec<_>>();
+        info!("{} {} histogram: {}", plugin, hook, buckets.join(", "));
+    }
+}
+
+#[derive(Debug, Default)]
+struct HookTimings {
+    transaction: LatencyHistogram,
+    block: LatencyHistogram,
+    entry: LatencyHistogram,
+    reward: LatencyHistogram,
+}
+
+/// Decorator that forwards every hook to the wrapped plugin and records
+/// per-hook latency histograms, reported when the plugin exits.
+///
+/// Latency covers the hook's future from creation to completion, including
+/// any time spent waiting on I/O such as database writes.
+pub struct TimedPlugin {
+    inner: Box<dyn Plugin>,
+    timings: Arc<HookTimings>,
+}
+
+impl TimedPlugin {
+    /// Wraps `inner` with timing instrumentation.
+    pub fn new(inner: Box<dyn Plugin>) -> Self {
+        Self {
+            inner,
+            timings: Arc::default(),
+        }
+    }
+
+    fn report(&self) {
+        let name = self.inner.name();
+        self.timings.transaction.report(name, "on_transaction");
+        self.timings.block.report(name, "on_block");
+        self.timings.entry.report(name, "on_entry");
+        self.timings.reward.report(name, "on_reward");
+    }
+}
+
+/// Runs `future` and records its latency into `histogram`.
+fn timed<'a>(future: PluginFuture<'a>, histogram: &'a LatencyHistogram) -> PluginFuture<'a> {
+    let started = Instant::now();
+    async move {
+        let result = future.await;
+        histogram.record(started.elapsed());
+        result
+    }
+    .boxed()
+}
+
+impl Plugin for TimedPlugin {
+    #[inline(always)]
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    #[inline(always)]
+    fn version(&self) -> u16 {
+        self.inner.version()
+    }
+
+    #[inline(always)]
+    fn id(&self) -> u16 {
+        self.inner.id()
+    }
+
+    #[inline(always)]
+    fn on_transaction<'a>(
+        &'a self,
+        thread_id: usize,
+        db: Option<Arc<Client>>,
+        transaction: &'a TransactionData,
+    ) -> PluginFuture<'a> {
+        timed(
+            self.inner.on_transaction(thread_id, db, transaction),
+            &self.timings.transaction,
+        )
+    }
+
+    #[inline(always)]
+    fn on_block<'a>(
+        &'a self,
+        thread_id: usize,
+        db: Option<Arc<Client>>,
+        block: &'a BlockData,
+    ) -> PluginFuture<'a> {
+        timed(
+            self.inner.on_block(thread_id, db, block),
+            &self.timings.block,
+        )
+    }
+
+    #[inline(always)]
+    fn on_entry<'a>(
+        &'a self,
+        thread_id: usize,
+        db: Option<Arc<Client>>,
+        entry: &'a EntryData,
+    ) -> PluginFuture<'a> {
+        timed(
+            self.inner.on_entry(thread_id, db, entry),
+            &self.timings.entry,
+        )
+    }
+
+    #[inline(always)]
+    fn on_reward<'a>(
+        &'a self,
+        thread_id: usize,
+        db: Option<Arc<Client>>,
+        reward: &'a RewardsData,
+    ) -> PluginFuture<'a> {
+        timed(
+            self.inner.on_reward(thread_id, db, reward),
+            &self.timings.reward,
+        )
+    }
+
+    #[inline(always)]
+    fn on_load(&self, db: Option<Arc<Client>>) -> PluginFuture<'_> {
+        self.inner.on_load(db)
+    }
+
+    #[inline(always)]
+    fn on_exit(&self, db: Option<Arc<Client>>) -> PluginFuture<'_> {
+        async move {
+            let result = self.inner.on_exit(db).await;
+            self.report();
+            result
+        }
+        .boxed()
+    }
+}
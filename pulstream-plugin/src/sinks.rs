@@ -0,0 +1,89 @@
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, ControlEvent, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent,
+    TransferEvent, UndecodedInstruction,
+};
+use crate::utils::{breadth::BreadthSnapshot, vwap::VwapSnapshot};
+use std::io;
+
+/// Length-prefixed bincode sink and its replay reader.
+pub mod bincode;
+/// Sink that feeds an async stream of events.
+pub mod channel;
+/// Sink wrapper that slows every write, for testing.
+pub mod delayed;
+/// Sink that logs each event.
+pub mod log;
+/// Sink that drops every event, for benchmarking.
+pub mod null;
+/// Output targets shared by the serializing sinks, including file rotation.
+pub mod output;
+/// Length-delimited protobuf sink for feeding trade events to another process.
+pub mod protobuf;
+/// Sink sending each kind of record to a configured sink.
+pub mod router;
+/// Object storage output for the serializing sinks.
+#[cfg(feature = "s3")]
+pub mod s3;
+/// Sink routing each mint's events to a separate sink.
+pub mod split;
+/// Newline-delimited JSON over TCP to a collector.
+#[cfg(feature = "tcp")]
+pub mod tcp;
+
+/// Destination for decoded trade events.
+pub trait TradeSink: Send + Sync {
+    /// Writes a single trade event.
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()>;
+
+    /// Writes a migration event. Sinks that only carry trades ignore it.
+    fn write_migration(&self, _event: &MigrationEvent) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a new holder event. Sinks that only carry trades ignore it.
+    fn write_new_holder(&self, _event: &NewHolderEvent) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a token transfer event. Sinks that only carry trades ignore it.
+    fn write_transfer(&self, _event: &TransferEvent) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes the summary of one block's trades. Sinks that only carry trades
+    /// ignore it.
+    fn write_block_summary(&self, _summary: &BlockSummary) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a payer's net position over the run. Sinks that only carry
+    /// trades ignore it.
+    fn write_net_position(&self, _position: &NetPosition) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a closed VWAP window. Sinks that only carry trades ignore it.
+    fn write_vwap(&self, _snapshot: &VwapSnapshot) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a closed breadth window. Sinks that only carry trades ignore it.
+    fn write_breadth(&self, _snapshot: &BreadthSnapshot) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes an instruction the decoder did not recognise. Sinks that only
+    /// carry trades ignore it.
+    fn write_undecoded(&self, _instruction: &UndecodedInstruction) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes a marker for the start or end of a run. Sinks that only carry
+    /// trades ignore it.
+    fn write_control(&self, _event: &ControlEvent) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes any buffered output.
+    fn flush(&self) -> io::Result<()>;
+}
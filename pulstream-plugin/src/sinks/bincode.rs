@@ -0,0 +1,201 @@
+use super::{
+    output::{open_output, OutputOptions},
+    TradeSink,
+};
+use crate::plugins::pumpfun_tracking::{OwnedTradeEvent, SCHEMA_VERSION};
+use std::{
+    io::{self, Read, Write},
+    sync::Mutex,
+};
+
+/// Writes each event as a little-endian `u32` length followed by the
+/// bincode-serialized [`OwnedTradeEvent`]. Read it back with
+/// [`replay_bincode`].
+pub struct BincodeSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl BincodeSink {
+    /// Creates a sink writing to `writer`.
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Opens `target` (see [`open_output`]).
+    pub fn open(target: &str, options: OutputOptions) -> io::Result<Self> {
+        Ok(Self::new(open_output(target, options)?))
+    }
+}
+
+impl TradeSink for BincodeSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        let payload = ::bincode::serialize(event).map_err(io::Error::other)?;
+        let len = u32::try_from(payload.len()).map_err(io::Error::other)?;
+        let mut record = Vec::with_capacity(4 + payload.len());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(&payload);
+        self.writer
+            .lock()
+            .map_err(|_| io::Error::other("bincode sink poisoned"))?
+            .write_all(&record)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.writer
+            .lock()
+            .map_err(|_| io::Error::other("bincode sink poisoned"))?
+            .flush()
+    }
+}
+
+/// Reads back the events written by a [`BincodeSink`].
+///
+/// The iterator ends at a clean end of input and yields an
+/// [`io::ErrorKind::UnexpectedEof`] error for a truncated record.
+///
+/// Records must have been written with the current [`SCHEMA_VERSION`]: the
+/// layout changes between versions, so a record of any other version yields
+/// an [`io::ErrorKind::InvalidData`] error naming its version rather than
+/// being misread. Records written before versioning show an arbitrary one.
+pub fn replay_bincode<R: Read>(reader: R) -> BincodeReplay<R> {
+    BincodeReplay { reader }
+}
+
+/// Iterator returned by [`replay_bincode`].
+pub struct BincodeReplay<R> {
+    reader: R,
+}
+
+impl<R: Read> BincodeReplay<R> {
+    fn read_record(&mut self) -> io::Result<Option<OwnedTradeEvent>> {
+        let mut len = [0u8; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.reader.read(&mut len[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+        self.reader.read_exact(&mut payload)?;
+        let version = payload
+            .first_chunk::<4>()
+            .map(|version| u32::from_le_bytes(*version))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "record too short"))?;
+        if version != SCHEMA_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported schema version {version} (expected {SCHEMA_VERSION}); \
+                     replay the file with the pulstream version that wrote it"
+                ),
+            ));
+        }
+        ::bincode::deserialize(&payload)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl<R: Read> Iterator for BincodeReplay<R> {
+    type Item = io::Result<OwnedTradeEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::pumpfun_tracking::PumpfunTrackingPlugin;
+    use crate::utils::self_test::{fixtures, FIXTURE_MINT};
+    use std::sync::Arc;
+
+    /// Writer whose output stays readable after the sink takes it.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The fixture trades, one with its optional fields set.
+    fn events() -> Vec<OwnedTradeEvent> {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let mut events: Vec<OwnedTradeEvent> = fixtures()
+            .iter()
+            .flat_map(|fixture| plugin.process_transaction(&fixture.transaction).unwrap())
+            .collect();
+        events[0].symbol = Some("TEST".to_string());
+        events[0].block_time = Some(1_700_000_000);
+        events[0].sandwich_role = Some("victim".to_string());
+        events
+    }
+
+    fn record(payload: &[u8]) -> Vec<u8> {
+        let mut record = (payload.len() as u32).to_le_bytes().to_vec();
+        record.extend_from_slice(payload);
+        record
+    }
+
+    #[test]
+    fn rejects_other_schema_versions() {
+        let mut payload = 1u32.to_le_bytes().to_vec();
+        payload.extend_from_slice(&[0; 64]);
+        let err = replay_bincode(record(&payload).as_slice())
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().contains("unsupported schema version 1"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_records_too_short_for_a_version() {
+        let err = replay_bincode(record(&[2, 0]).as_slice())
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn round_trips_written_events() {
+        let buffer = SharedBuffer::default();
+        let sink = BincodeSink::new(Box::new(buffer.clone()));
+        let events = events();
+        for event in &events {
+            sink.write_event(event).unwrap();
+        }
+        sink.flush().unwrap();
+        let written = buffer.0.lock().unwrap().clone();
+        let replayed = replay_bincode(written.as_slice())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(replayed, events);
+
+        let truncated = &written[..written.len() - 1];
+        let mut replay = replay_bincode(truncated);
+        assert_eq!(replay.next().unwrap().unwrap(), events[0]);
+        assert_eq!(
+            replay.next().unwrap().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
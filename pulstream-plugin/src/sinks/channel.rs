@@ -0,0 +1,44 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::OwnedTradeEvent;
+use futures::{
+    channel::mpsc::{self, Receiver, Sender},
+    executor::block_on,
+    SinkExt,
+};
+use std::io;
+
+/// Async stream of trade events fed by a [`ChannelSink`].
+pub type TradeEventStream = Receiver<OwnedTradeEvent>;
+
+/// Sends each trade event into a bounded channel consumed as a
+/// [`TradeEventStream`].
+///
+/// When the channel is full, writing blocks the firehose thread until the
+/// consumer catches up, so a slow consumer slows the run down instead of
+/// losing events. The consumer must therefore run on a different runtime or
+/// thread than the runner.
+#[derive(Debug, Clone)]
+pub struct ChannelSink {
+    sender: Sender<OwnedTradeEvent>,
+}
+
+impl ChannelSink {
+    /// Creates a sink and the stream receiving its events, buffering up to
+    /// `capacity` events.
+    pub fn new(capacity: usize) -> (Self, TradeEventStream) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        (Self { sender }, receiver)
+    }
+}
+
+impl TradeSink for ChannelSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        let mut sender = self.sender.clone();
+        block_on(sender.send(event.clone()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "trade event stream closed"))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, ControlEvent, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent,
+    TransferEvent, UndecodedInstruction,
+};
+use crate::utils::{breadth::BreadthSnapshot, vwap::VwapSnapshot};
+use std::{io, sync::Arc, thread, time::Duration};
+
+/// Sleeps for a fixed delay before every write to the inner sink, to imitate a
+/// slow output. For testing backpressure and shutdown only; it blocks the
+/// calling thread, which is a firehose worker.
+pub struct DelayedSink {
+    inner: Arc<dyn TradeSink>,
+    delay: Duration,
+}
+
+impl DelayedSink {
+    /// Wraps `inner`, delaying each of its writes by `delay`. Flushes are not
+    /// delayed.
+    pub fn new(inner: Arc<dyn TradeSink>, delay: Duration) -> Self {
+        Self { inner, delay }
+    }
+}
+
+impl TradeSink for DelayedSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_event(event)
+    }
+
+    fn write_migration(&self, event: &MigrationEvent) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_migration(event)
+    }
+
+    fn write_new_holder(&self, event: &NewHolderEvent) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_new_holder(event)
+    }
+
+    fn write_transfer(&self, event: &TransferEvent) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_transfer(event)
+    }
+
+    fn write_block_summary(&self, summary: &BlockSummary) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_block_summary(summary)
+    }
+
+    fn write_net_position(&self, position: &NetPosition) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_net_position(position)
+    }
+
+    fn write_vwap(&self, snapshot: &VwapSnapshot) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_vwap(snapshot)
+    }
+
+    fn write_breadth(&self, snapshot: &BreadthSnapshot) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_breadth(snapshot)
+    }
+
+    fn write_undecoded(&self, instruction: &UndecodedInstruction) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_undecoded(instruction)
+    }
+
+    fn write_control(&self, event: &ControlEvent) -> io::Result<()> {
+        thread::sleep(self.delay);
+        self.inner.write_control(event)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
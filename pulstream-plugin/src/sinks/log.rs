@@ -0,0 +1,210 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, ControlEvent, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent,
+    TransferEvent, UndecodedInstruction,
+};
+use crate::utils::{breadth::BreadthSnapshot, vwap::VwapSnapshot};
+use std::io;
+
+/// Logs each trade event at info level.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogSink;
+
+impl TradeSink for LogSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        let accounts = event
+            .accounts
+            .iter()
+            .map(|account| {
+                format!(
+                    "{}{}{}",
+                    account.pubkey,
+                    if account.is_signer { " (signer)" } else { "" },
+                    if account.is_writable {
+                        " (writable)"
+                    } else {
+                        ""
+                    }
+                )
+            })
+            .collect::<Vec<_>>();
+        log::info!(
+            "Trade event:  Slot: {:?}, Signature: {:?}, Instruction Path: {:?}, Timestamp: {:?}, Program Timestamp: {:?}, Block Time: {:?}, Ingest Time: {:?}, Program ID: {:?}, Program Stack: {:?}, Mint: {:?}, Payer: {:?}, Amount In: {:?}, Amount Out: {:?}, Is Buy: {:?}, Priority Fee: {:?}, Symbol: {:?}, EWMA Price: {:?}, Curve Progress: {:?}, Direction Mismatch: {:?}, Sandwich Role: {:?}, Return Data: {:?}, Processing Latency (ms): {:?}, Accounts: {:?}",
+            event.slot,
+            event.signature,
+            event.instruction_path,
+            event.timestamp,
+            event.program_timestamp,
+            event.block_time,
+            event.ingest_time,
+            event.program_id,
+            event.program_stack,
+            event.mint,
+            event.payer,
+            event.amount_in,
+            event.amount_out,
+            event.is_buy,
+            event.priority_fee_micro_lamports,
+            event.symbol,
+            event.ewma_price,
+            event.curve_progress,
+            event.direction_mismatch,
+            event.sandwich_role,
+            event.return_data,
+            event.processing_latency_ms,
+            accounts
+        );
+        Ok(())
+    }
+
+    fn write_migration(&self, event: &MigrationEvent) -> io::Result<()> {
+        log::info!(
+            "Migration event:  Slot: {:?}, Signature: {:?}, Instruction Path: {:?}, Timestamp: {:?}, Mint: {:?}, Pool: {:?}, Bonding Curve: {:?}, User: {:?}, Mint Amount: {:?}, SOL Amount: {:?}, Pool Migration Fee: {:?}",
+            event.slot,
+            event.signature,
+            event.instruction_path,
+            event.timestamp,
+            event.mint,
+            event.pool,
+            event.bonding_curve,
+            event.user,
+            event.mint_amount,
+            event.sol_amount,
+            event.pool_migration_fee
+        );
+        Ok(())
+    }
+
+    fn write_new_holder(&self, event: &NewHolderEvent) -> io::Result<()> {
+        log::info!(
+            "New holder event:  Slot: {:?}, Signature: {:?}, Instruction Path: {:?}, Owner: {:?}, Mint: {:?}, Token Account: {:?}",
+            event.slot,
+            event.signature,
+            event.instruction_path,
+            event.owner,
+            event.mint,
+            event.token_account
+        );
+        Ok(())
+    }
+
+    fn write_block_summary(&self, summary: &BlockSummary) -> io::Result<()> {
+        log::info!(
+            "Block summary:  Slot: {:?}, Block Time: {:?}, Mint: {:?}, Buys: {:?}, Sells: {:?}, SOL Volume: {:?}, Token Volume: {:?}, Net SOL: {:?}, Net Tokens: {:?}",
+            summary.slot,
+            summary.block_time,
+            summary.mint,
+            summary.buys,
+            summary.sells,
+            summary.sol_volume,
+            summary.token_volume,
+            summary.net_sol,
+            summary.net_tokens
+        );
+        Ok(())
+    }
+
+    fn write_net_position(&self, position: &NetPosition) -> io::Result<()> {
+        log::info!(
+            "Net position:  Mint: {:?}, Payer: {:?}, Buys: {:?}, Sells: {:?}, Net SOL: {:?}, Net Tokens: {:?}",
+            position.mint,
+            position.payer,
+            position.buys,
+            position.sells,
+            position.net_sol,
+            position.net_tokens
+        );
+        Ok(())
+    }
+
+    fn write_vwap(&self, snapshot: &VwapSnapshot) -> io::Result<()> {
+        log::info!(
+            "VWAP snapshot:  Mint: {:?}, Window Start: {:?}, Window End: {:?}, VWAP: {:?}, Token Volume: {:?}, SOL Volume: {:?}, Trades: {:?}",
+            snapshot.mint,
+            snapshot.window_start,
+            snapshot.window_end,
+            snapshot.vwap,
+            snapshot.token_volume,
+            snapshot.sol_volume,
+            snapshot.trades
+        );
+        Ok(())
+    }
+
+    fn write_breadth(&self, snapshot: &BreadthSnapshot) -> io::Result<()> {
+        log::info!(
+            "Breadth snapshot:  Window Start Slot: {:?}, Window End Slot: {:?}, Unique Mints: {:?}",
+            snapshot.window_start_slot,
+            snapshot.window_end_slot,
+            snapshot.unique_mints
+        );
+        Ok(())
+    }
+
+    fn write_transfer(&self, event: &TransferEvent) -> io::Result<()> {
+        log::info!(
+            "Transfer event:  Slot: {:?}, Signature: {:?}, Instruction Path: {:?}, Mint: {:?}, Source: {:?}, Source Owner: {:?}, Destination: {:?}, Destination Owner: {:?}, Authority: {:?}, Amount: {:?}",
+            event.slot,
+            event.signature,
+            event.instruction_path,
+            event.mint,
+            event.source,
+            event.source_owner,
+            event.destination,
+            event.destination_owner,
+            event.authority,
+            event.amount
+        );
+        Ok(())
+    }
+
+    fn write_undecoded(&self, instruction: &UndecodedInstruction) -> io::Result<()> {
+        log::info!(
+            "Undecoded instruction:  Slot: {:?}, Signature: {:?}, Instruction Path: {:?}, Stack Height: {:?}, Program ID: {:?}, Data: {}, Accounts: {:?}",
+            instruction.slot,
+            instruction.signature,
+            instruction.instruction_path,
+            instruction.stack_height,
+            instruction.program_id,
+            instruction.data,
+            instruction.accounts
+        );
+        Ok(())
+    }
+
+    fn write_control(&self, event: &ControlEvent) -> io::Result<()> {
+        match event {
+            ControlEvent::RunStarted {
+                schema_version,
+                mint,
+                start_slot,
+                end_slot,
+            } => log::info!(
+                "Run started:  Mint: {:?}, Start Slot: {:?}, End Slot: {:?}, Schema Version: {:?}",
+                mint,
+                start_slot,
+                end_slot,
+                schema_version
+            ),
+            ControlEvent::RunFinished {
+                mint,
+                start_slot,
+                end_slot,
+                last_slot,
+                events,
+            } => log::info!(
+                "Run finished:  Mint: {:?}, Start Slot: {:?}, End Slot: {:?}, Last Slot: {:?}, Events: {:?}",
+                mint,
+                start_slot,
+                end_slot,
+                last_slot,
+                events
+            ),
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
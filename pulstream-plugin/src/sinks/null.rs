@@ -0,0 +1,123 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent, TransferEvent,
+    UndecodedInstruction,
+};
+use crate::utils::{breadth::BreadthSnapshot, vwap::VwapSnapshot};
+use std::{
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Drops every event, only counting them. Used to measure decode and transform
+/// throughput without any output cost.
+#[derive(Debug, Default)]
+pub struct NullSink {
+    events: AtomicU64,
+    migrations: AtomicU64,
+    new_holders: AtomicU64,
+    transfers: AtomicU64,
+    block_summaries: AtomicU64,
+    net_positions: AtomicU64,
+    vwap_snapshots: AtomicU64,
+    breadth_snapshots: AtomicU64,
+    undecoded: AtomicU64,
+}
+
+impl NullSink {
+    /// Returns the number of trade events dropped so far.
+    pub fn events(&self) -> u64 {
+        self.events.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of migration events dropped so far.
+    pub fn migrations(&self) -> u64 {
+        self.migrations.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of new holder events dropped so far.
+    pub fn new_holders(&self) -> u64 {
+        self.new_holders.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of transfer events dropped so far.
+    pub fn transfers(&self) -> u64 {
+        self.transfers.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of block summaries dropped so far.
+    pub fn block_summaries(&self) -> u64 {
+        self.block_summaries.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of net positions dropped so far.
+    pub fn net_positions(&self) -> u64 {
+        self.net_positions.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of VWAP snapshots dropped so far.
+    pub fn vwap_snapshots(&self) -> u64 {
+        self.vwap_snapshots.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of breadth snapshots dropped so far.
+    pub fn breadth_snapshots(&self) -> u64 {
+        self.breadth_snapshots.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of undecoded instructions dropped so far.
+    pub fn undecoded(&self) -> u64 {
+        self.undecoded.load(Ordering::Relaxed)
+    }
+}
+
+impl TradeSink for NullSink {
+    fn write_event(&self, _event: &OwnedTradeEvent) -> io::Result<()> {
+        self.events.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_migration(&self, _event: &MigrationEvent) -> io::Result<()> {
+        self.migrations.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_new_holder(&self, _event: &NewHolderEvent) -> io::Result<()> {
+        self.new_holders.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_transfer(&self, _event: &TransferEvent) -> io::Result<()> {
+        self.transfers.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_block_summary(&self, _summary: &BlockSummary) -> io::Result<()> {
+        self.block_summaries.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_net_position(&self, _position: &NetPosition) -> io::Result<()> {
+        self.net_positions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_vwap(&self, _snapshot: &VwapSnapshot) -> io::Result<()> {
+        self.vwap_snapshots.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_breadth(&self, _snapshot: &BreadthSnapshot) -> io::Result<()> {
+        self.breadth_snapshots.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_undecoded(&self, _instruction: &UndecodedInstruction) -> io::Result<()> {
+        self.undecoded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// When a file output rolls over to the next sequence-numbered file.
+///
+/// A threshold of `None` disables that trigger; with both disabled the output
+/// is a single file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Roll once the current file holds at least this many bytes.
+    pub max_bytes: Option<u64>,
+    /// Roll once the current file holds this many records.
+    pub max_records: Option<u64>,
+}
+
+impl RotationPolicy {
+    /// Returns `true` if any threshold is set.
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_records.is_some()
+    }
+}
+
+/// How file outputs are split and synced to disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    pub rotation: RotationPolicy,
+    /// Flush and fsync file outputs at most this long after a write. Without it
+    /// data reaches disk only when the buffer fills or on a clean exit.
+    pub sync_interval: Option<Duration>,
+}
+
+/// Opens an output target: `-` for stdout, `unix:<path>` for a Unix socket,
+/// `s3://<bucket>/<key>` for object storage (with the `s3` feature), or a file
+/// path. Rotation is supported for files and S3; periodic syncing only for
+/// files.
+pub fn open_output(target: &str, options: OutputOptions) -> io::Result<Box<dyn Write + Send>> {
+    let is_file = !(target == "-" || target.starts_with("unix:") || target.starts_with("s3://"));
+    if !is_file && options.sync_interval.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("sync interval requires a file output, got {target}"),
+        ));
+    }
+    if target.starts_with("s3://") {
+        return open_s3(target, options);
+    }
+    if target == "-" || target.starts_with("unix:") {
+        if options.rotation.is_enabled() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("output rotation requires a file output, got {target}"),
+            ));
+        }
+        if let Some(path) = target.strip_prefix("unix:") {
+            return Ok(Box::new(BufWriter::new(connect_unix(path)?)));
+        }
+        return Ok(Box::new(BufWriter::new(io::stdout())));
+    }
+    if options.rotation.is_enabled() {
+        Ok(Box::new(RotatingFile::create(
+            target,
+            options.rotation,
+            options.sync_interval,
+        )?))
+    } else {
+        Ok(Box::new(SyncedFile::create(target, options.sync_interval)?))
+    }
+}
+
+/// Buffered file writer that flushes and fsyncs once `sync_interval` has
+/// passed since the last sync.
+///
+/// Syncing is checked on write, so an idle output is synced by the next write
+/// or by an explicit flush. A crash loses at most the events written since the
+/// last sync.
+pub struct SyncedFile {
+    file: BufWriter<File>,
+    sync_interval: Option<Duration>,
+    last_sync: Instant,
+}
+
+impl SyncedFile {
+    /// Creates (or truncates) the file at `path`.
+    pub fn create(path: impl AsRef<Path>, sync_interval: Option<Duration>) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+            sync_interval,
+            last_sync: Instant::now(),
+        })
+    }
+
+    /// Opens the file at `path` for appending, creating it if needed.
+    pub fn append(path: impl AsRef<Path>, sync_interval: Option<Duration>) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::options().append(true).create(true).open(path)?),
+            sync_interval,
+            last_sync: Instant::now(),
+        })
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()?;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+}
+
+impl Write for SyncedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        if self
+            .sync_interval
+            .is_some_and(|interval| self.last_sync.elapsed() >= interval)
+        {
+            self.sync()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.sync_interval.is_some() {
+            self.sync()
+        } else {
+            self.file.flush()
+        }
+    }
+}
+
+#[cfg(feature = "s3")]
+fn open_s3(target: &str, options: OutputOptions) -> io::Result<Box<dyn Write + Send>> {
+    Ok(Box::new(super::s3::S3Output::open(target, options)?))
+}
+
+#[cfg(not(feature = "s3"))]
+fn open_s3(target: &str, _options: OutputOptions) -> io::Result<Box<dyn Write + Send>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{target}: built without the `s3` feature"),
+    ))
+}
+
+#[cfg(unix)]
+fn connect_unix(path: &str) -> io::Result<std::os::unix::net::UnixStream> {
+    std::os::unix::net::UnixStream::connect(path)
+}
+
+#[cfg(not(unix))]
+fn connect_unix(_path: &str) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix sockets are not supported on this platform",
+    ))
+}
+
+/// File writer that rolls over to `<stem>.<NNNNN>.<ext>` once a
+/// [`RotationPolicy`] threshold is reached.
+///
+/// Each `write` call is treated as one complete record, so files are only ever
+/// split between records. Sinks must therefore hand over whole records and not
+/// wrap this in another buffer.
+pub struct RotatingFile {
+    base: PathBuf,
+    policy: RotationPolicy,
+    sync_interval: Option<Duration>,
+    index: u32,
+    file: SyncedFile,
+    bytes: u64,
+    records: u64,
+}
+
+impl RotatingFile {
+    /// Creates the first file of the sequence.
+    pub fn create(
+        base: impl AsRef<Path>,
+        policy: RotationPolicy,
+        sync_interval: Option<Duration>,
+    ) -> io::Result<Self> {
+        let base = base.as_ref().to_path_buf();
+        let file = SyncedFile::create(sequence_path(&base, 0), sync_interval)?;
+        Ok(Self {
+            base,
+            policy,
+            sync_interval,
+            index: 0,
+            file,
+            bytes: 0,
+            records: 0,
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.policy.max_bytes.is_some_and(|max| self.bytes >= max)
+            || self
+                .policy
+                .max_records
+                .is_some_and(|max| self.records >= max)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.index += 1;
+        self.file = SyncedFile::create(sequence_path(&self.base, self.index), self.sync_interval)?;
+        self.bytes = 0;
+        self.records = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.records > 0 && self.is_full() {
+            self.rotate()?;
+        }
+        self.file.write_all(buf)?;
+        self.bytes += buf.len() as u64;
+        self.records += 1;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Inserts a zero-padded sequence number before the extension of `base`.
+pub(crate) fn sequence_path(base: &Path, index: u32) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match base.extension() {
+        Some(ext) => format!("{stem}.{index:05}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{index:05}"),
+    };
+    base.with_file_name(name)
+}
@@ -0,0 +1,263 @@
+use super::{
+    output::{open_output, OutputOptions},
+    TradeSink,
+};
+use crate::plugins::pumpfun_tracking::{AccountRef, OwnedTradeEvent};
+use prost::Message;
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+};
+
+/// Protobuf encoding of a trade event, mirroring `proto/trade_event.proto`.
+#[derive(Clone, PartialEq, Message)]
+pub struct TradeEventMessage {
+    #[prost(string, tag = "1")]
+    pub signature: String,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(int64, tag = "3")]
+    pub timestamp: i64,
+    #[prost(string, tag = "4")]
+    pub program_id: String,
+    #[prost(string, tag = "5")]
+    pub mint: String,
+    #[prost(string, tag = "6")]
+    pub payer: String,
+    #[prost(uint64, tag = "7")]
+    pub amount_in: u64,
+    #[prost(uint64, tag = "8")]
+    pub amount_out: u64,
+    #[prost(bool, tag = "9")]
+    pub is_buy: bool,
+    #[prost(string, tag = "10")]
+    pub instruction_path: String,
+    #[prost(uint64, optional, tag = "11")]
+    pub priority_fee_micro_lamports: Option<u64>,
+    #[prost(message, repeated, tag = "12")]
+    pub accounts: Vec<AccountRefMessage>,
+    #[prost(string, optional, tag = "13")]
+    pub symbol: Option<String>,
+    #[prost(double, optional, tag = "14")]
+    pub ewma_price: Option<f64>,
+    #[prost(int64, optional, tag = "15")]
+    pub block_time: Option<i64>,
+    #[prost(string, optional, tag = "16")]
+    pub return_data: Option<String>,
+    #[prost(int64, optional, tag = "17")]
+    pub processing_latency_ms: Option<i64>,
+    #[prost(double, optional, tag = "18")]
+    pub curve_progress: Option<f64>,
+    #[prost(int64, tag = "19")]
+    pub program_timestamp: i64,
+    #[prost(int64, optional, tag = "20")]
+    pub ingest_time: Option<i64>,
+    #[prost(bool, optional, tag = "21")]
+    pub direction_mismatch: Option<bool>,
+    #[prost(string, repeated, tag = "22")]
+    pub program_stack: Vec<String>,
+    #[prost(uint32, tag = "23")]
+    pub schema_version: u32,
+    #[prost(string, optional, tag = "24")]
+    pub sandwich_role: Option<String>,
+}
+
+/// Protobuf encoding of an [`AccountRef`].
+#[derive(Clone, PartialEq, Message)]
+pub struct AccountRefMessage {
+    #[prost(string, tag = "1")]
+    pub pubkey: String,
+    #[prost(bool, tag = "2")]
+    pub is_signer: bool,
+    #[prost(bool, tag = "3")]
+    pub is_writable: bool,
+}
+
+impl From<&AccountRef> for AccountRefMessage {
+    fn from(account: &AccountRef) -> Self {
+        Self {
+            pubkey: account.pubkey.clone(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }
+    }
+}
+
+impl From<AccountRefMessage> for AccountRef {
+    fn from(message: AccountRefMessage) -> Self {
+        Self {
+            pubkey: message.pubkey,
+            is_signer: message.is_signer,
+            is_writable: message.is_writable,
+        }
+    }
+}
+
+impl From<&OwnedTradeEvent> for TradeEventMessage {
+    fn from(event: &OwnedTradeEvent) -> Self {
+        Self {
+            signature: event.signature.clone(),
+            slot: event.slot,
+            timestamp: event.timestamp,
+            program_id: event.program_id.clone(),
+            mint: event.mint.clone(),
+            payer: event.payer.clone(),
+            amount_in: event.amount_in,
+            amount_out: event.amount_out,
+            is_buy: event.is_buy,
+            instruction_path: event.instruction_path.clone(),
+            priority_fee_micro_lamports: event.priority_fee_micro_lamports,
+            accounts: event.accounts.iter().map(AccountRefMessage::from).collect(),
+            symbol: event.symbol.clone(),
+            ewma_price: event.ewma_price,
+            curve_progress: event.curve_progress,
+            program_timestamp: event.program_timestamp,
+            ingest_time: event.ingest_time,
+            direction_mismatch: event.direction_mismatch,
+            sandwich_role: event.sandwich_role.clone(),
+            program_stack: event.program_stack.clone(),
+            schema_version: event.schema_version,
+            block_time: event.block_time,
+            return_data: event.return_data.clone(),
+            processing_latency_ms: event.processing_latency_ms,
+        }
+    }
+}
+
+impl From<TradeEventMessage> for OwnedTradeEvent {
+    fn from(message: TradeEventMessage) -> Self {
+        Self {
+            signature: message.signature,
+            slot: message.slot,
+            timestamp: message.timestamp,
+            program_id: message.program_id,
+            mint: message.mint,
+            payer: message.payer,
+            amount_in: message.amount_in,
+            amount_out: message.amount_out,
+            is_buy: message.is_buy,
+            instruction_path: message.instruction_path,
+            priority_fee_micro_lamports: message.priority_fee_micro_lamports,
+            accounts: message.accounts.into_iter().map(AccountRef::from).collect(),
+            symbol: message.symbol,
+            ewma_price: message.ewma_price,
+            curve_progress: message.curve_progress,
+            program_timestamp: message.program_timestamp,
+            ingest_time: message.ingest_time,
+            direction_mismatch: message.direction_mismatch,
+            sandwich_role: message.sandwich_role,
+            program_stack: message.program_stack,
+            schema_version: message.schema_version,
+            block_time: message.block_time,
+            return_data: message.return_data,
+            processing_latency_ms: message.processing_latency_ms,
+        }
+    }
+}
+
+/// Writes each event as a varint length-prefixed [`TradeEventMessage`].
+pub struct ProtobufSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl ProtobufSink {
+    /// Creates a sink writing to `writer`.
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Opens `target` (see [`open_output`]).
+    pub fn open(target: &str, options: OutputOptions) -> io::Result<Self> {
+        Ok(Self::new(open_output(target, options)?))
+    }
+}
+
+impl TradeSink for ProtobufSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        let buf = TradeEventMessage::from(event).encode_length_delimited_to_vec();
+        self.writer
+            .lock()
+            .map_err(|_| io::Error::other("protobuf sink poisoned"))?
+            .write_all(&buf)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.writer
+            .lock()
+            .map_err(|_| io::Error::other("protobuf sink poisoned"))?
+            .flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::pumpfun_tracking::PumpfunTrackingPlugin;
+    use crate::utils::self_test::{fixtures, FIXTURE_MINT};
+    use std::sync::Arc;
+
+    /// Writer whose output stays readable after the sink takes it.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The fixture trades, the first with every optional field set.
+    fn events() -> Vec<OwnedTradeEvent> {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        let mut events: Vec<OwnedTradeEvent> = fixtures()
+            .iter()
+            .flat_map(|fixture| plugin.process_transaction(&fixture.transaction).unwrap())
+            .collect();
+        let event = &mut events[0];
+        event.priority_fee_micro_lamports = Some(5_000);
+        event.accounts = vec![AccountRef {
+            pubkey: FIXTURE_MINT.to_string(),
+            is_signer: false,
+            is_writable: true,
+        }];
+        event.symbol = Some("TEST".to_string());
+        event.ewma_price = Some(0.25);
+        event.block_time = Some(1_700_000_000);
+        event.return_data = Some("AQID".to_string());
+        event.processing_latency_ms = Some(1_500);
+        event.curve_progress = Some(42.5);
+        event.ingest_time = Some(1_700_000_001);
+        event.direction_mismatch = Some(true);
+        event.program_stack = vec!["router".to_string(), "pumpfun".to_string()];
+        event.sandwich_role = Some("victim".to_string());
+        events
+    }
+
+    #[test]
+    fn round_trips_written_events() {
+        let buffer = SharedBuffer::default();
+        let sink = ProtobufSink::new(Box::new(buffer.clone()));
+        let events = events();
+        assert!(events.len() > 1);
+        for event in &events {
+            sink.write_event(event).unwrap();
+        }
+        sink.flush().unwrap();
+
+        let written = buffer.0.lock().unwrap().clone();
+        let mut remaining = written.as_slice();
+        let mut decoded = Vec::new();
+        while !remaining.is_empty() {
+            let message = TradeEventMessage::decode_length_delimited(&mut remaining).unwrap();
+            decoded.push(OwnedTradeEvent::from(message));
+        }
+        assert_eq!(decoded, events);
+    }
+}
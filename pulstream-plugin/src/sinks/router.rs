@@ -0,0 +1,193 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, ControlEvent, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent,
+    TransferEvent, UndecodedInstruction,
+};
+use crate::utils::{breadth::BreadthSnapshot, vwap::VwapSnapshot};
+use std::{collections::HashMap, fmt, io, str::FromStr, sync::Arc};
+
+/// Kind of record a sink can receive, used as the key of a routing table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Trade,
+    Migration,
+    NewHolder,
+    Transfer,
+    BlockSummary,
+    NetPosition,
+    Vwap,
+    Breadth,
+    Undecoded,
+    Control,
+}
+
+impl EventKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [EventKind; 10] = [
+        Self::Trade,
+        Self::Migration,
+        Self::NewHolder,
+        Self::Transfer,
+        Self::BlockSummary,
+        Self::NetPosition,
+        Self::Vwap,
+        Self::Breadth,
+        Self::Undecoded,
+        Self::Control,
+    ];
+
+    /// Returns the name used in routing configuration, e.g. `new_holder`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Trade => "trade",
+            Self::Migration => "migration",
+            Self::NewHolder => "new_holder",
+            Self::Transfer => "transfer",
+            Self::BlockSummary => "block_summary",
+            Self::NetPosition => "net_position",
+            Self::Vwap => "vwap",
+            Self::Breadth => "breadth",
+            Self::Undecoded => "undecoded",
+            Self::Control => "control",
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|kind| kind.name()).collect();
+                format!("unknown event type: {s} (expected {})", names.join(", "))
+            })
+    }
+}
+
+/// Sends each kind of record to the sink named for it in a routing table,
+/// for example trades to a protobuf file and migrations to the log.
+///
+/// Records of a kind without a route go to the fallback sink, if any, and
+/// otherwise fail with an error naming the kind, so a missing route is not
+/// silently dropped. Run markers without a route go to every sink instead.
+pub struct RoutingSink {
+    routes: HashMap<EventKind, Arc<dyn TradeSink>>,
+    fallback: Option<Arc<dyn TradeSink>>,
+    /// Every named sink, each once, for flushing.
+    sinks: Vec<Arc<dyn TradeSink>>,
+}
+
+impl RoutingSink {
+    /// Builds the router from named `sinks`, a table of kind to sink name
+    /// and an optional fallback sink name. Fails if a route or the fallback
+    /// names a sink that is not configured.
+    pub fn new(
+        sinks: HashMap<String, Arc<dyn TradeSink>>,
+        routes: HashMap<EventKind, String>,
+        fallback: Option<&str>,
+    ) -> Result<Self, String> {
+        let lookup = |name: &str, used_by: &str| {
+            sinks
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("{used_by} is routed to unknown sink {name:?}"))
+        };
+        let fallback = fallback
+            .map(|name| lookup(name, "the fallback"))
+            .transpose()?;
+        let mut resolved = HashMap::new();
+        for (kind, name) in routes {
+            resolved.insert(kind, lookup(&name, &format!("event type {kind}"))?);
+        }
+        Ok(Self {
+            routes: resolved,
+            fallback,
+            sinks: sinks.into_values().collect(),
+        })
+    }
+
+    fn route(
+        &self,
+        kind: EventKind,
+        write: impl Fn(&dyn TradeSink) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match self.routes.get(&kind).or(self.fallback.as_ref()) {
+            Some(sink) => write(sink.as_ref()),
+            None => Err(io::Error::other(format!(
+                "no route configured for {kind} events"
+            ))),
+        }
+    }
+}
+
+impl TradeSink for RoutingSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        self.route(EventKind::Trade, |sink| sink.write_event(event))
+    }
+
+    fn write_migration(&self, event: &MigrationEvent) -> io::Result<()> {
+        self.route(EventKind::Migration, |sink| sink.write_migration(event))
+    }
+
+    fn write_new_holder(&self, event: &NewHolderEvent) -> io::Result<()> {
+        self.route(EventKind::NewHolder, |sink| sink.write_new_holder(event))
+    }
+
+    fn write_transfer(&self, event: &TransferEvent) -> io::Result<()> {
+        self.route(EventKind::Transfer, |sink| sink.write_transfer(event))
+    }
+
+    fn write_block_summary(&self, summary: &BlockSummary) -> io::Result<()> {
+        self.route(EventKind::BlockSummary, |sink| {
+            sink.write_block_summary(summary)
+        })
+    }
+
+    fn write_net_position(&self, position: &NetPosition) -> io::Result<()> {
+        self.route(EventKind::NetPosition, |sink| {
+            sink.write_net_position(position)
+        })
+    }
+
+    fn write_vwap(&self, snapshot: &VwapSnapshot) -> io::Result<()> {
+        self.route(EventKind::Vwap, |sink| sink.write_vwap(snapshot))
+    }
+
+    fn write_breadth(&self, snapshot: &BreadthSnapshot) -> io::Result<()> {
+        self.route(EventKind::Breadth, |sink| sink.write_breadth(snapshot))
+    }
+
+    fn write_undecoded(&self, instruction: &UndecodedInstruction) -> io::Result<()> {
+        self.route(EventKind::Undecoded, |sink| {
+            sink.write_undecoded(instruction)
+        })
+    }
+
+    /// Run markers are written to every configured sink unless routed, since
+    /// each sink's consumer needs its own brackets.
+    fn write_control(&self, event: &ControlEvent) -> io::Result<()> {
+        if self.routes.contains_key(&EventKind::Control) {
+            return self.route(EventKind::Control, |sink| sink.write_control(event));
+        }
+        for sink in &self.sinks {
+            sink.write_control(event)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        for sink in &self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use super::output::{sequence_path, OutputOptions};
+use object_store::{aws::AmazonS3Builder, path::Path as ObjectPath, ObjectStore, WriteMultipart};
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::mpsc,
+    thread::{self, JoinHandle},
+};
+
+enum Job {
+    Upload {
+        key: String,
+        bytes: Vec<u8>,
+    },
+    /// Replies with the first upload error since the previous barrier.
+    Barrier(mpsc::Sender<io::Result<()>>),
+}
+
+/// Writer that buffers output in memory and uploads it to S3-compatible
+/// object storage as `<key stem>.<NNNNN>.<ext>` objects.
+///
+/// An object is uploaded (as a multipart upload) when a rotation threshold is
+/// reached and on `flush`, which waits for all pending uploads. Without
+/// rotation the whole output is held in memory until exit. Like
+/// [`super::output::RotatingFile`], each `write` call is one record.
+///
+/// Credentials, region and endpoint come from the standard `AWS_*`
+/// environment variables.
+pub struct S3Output {
+    key: String,
+    options: OutputOptions,
+    index: u32,
+    buffer: Vec<u8>,
+    records: u64,
+    jobs: Option<mpsc::Sender<Job>>,
+    uploader: Option<JoinHandle<()>>,
+}
+
+impl S3Output {
+    /// Opens `s3://<bucket>/<key>`. A key ending in `/` is treated as a prefix
+    /// and objects are named `trades.<NNNNN>` inside it.
+    pub fn open(url: &str, options: OutputOptions) -> io::Result<Self> {
+        let location = url
+            .strip_prefix("s3://")
+            .ok_or_else(|| invalid_input(format!("not an s3:// url: {url}")))?;
+        let (bucket, key) = location.split_once('/').unwrap_or((location, ""));
+        if bucket.is_empty() {
+            return Err(invalid_input(format!("missing bucket in {url}")));
+        }
+        let key = if key.is_empty() || key.ends_with('/') {
+            format!("{key}trades")
+        } else {
+            key.to_string()
+        };
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(io::Error::other)?;
+        let (jobs, receiver) = mpsc::channel();
+        let uploader = thread::Builder::new()
+            .name("pulstream-s3-upload".to_string())
+            .spawn(move || upload_loop(store, receiver))?;
+        Ok(Self {
+            key,
+            options,
+            index: 0,
+            buffer: Vec::new(),
+            records: 0,
+            jobs: Some(jobs),
+            uploader: Some(uploader),
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        let rotation = self.options.rotation;
+        rotation
+            .max_bytes
+            .is_some_and(|max| self.buffer.len() as u64 >= max)
+            || rotation.max_records.is_some_and(|max| self.records >= max)
+    }
+
+    fn send(&self, job: Job) -> io::Result<()> {
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(job).ok())
+            .ok_or_else(|| io::Error::other("s3 uploader stopped"))
+    }
+
+    /// Hands the buffered object to the uploader and starts the next one.
+    fn finish_object(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let key = sequence_path(Path::new(&self.key), self.index)
+            .to_string_lossy()
+            .into_owned();
+        let bytes = std::mem::take(&mut self.buffer);
+        self.send(Job::Upload { key, bytes })?;
+        self.index += 1;
+        self.records = 0;
+        Ok(())
+    }
+}
+
+impl Write for S3Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.records > 0 && self.is_full() {
+            self.finish_object()?;
+        }
+        self.buffer.extend_from_slice(buf);
+        self.records += 1;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish_object()?;
+        let (reply, done) = mpsc::channel();
+        self.send(Job::Barrier(reply))?;
+        done.recv()
+            .map_err(|_| io::Error::other("s3 uploader stopped"))?
+    }
+}
+
+impl Drop for S3Output {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            log::error!("Failed to upload final s3 object: {}", err);
+        }
+        self.jobs.take();
+        if let Some(uploader) = self.uploader.take() {
+            let _ = uploader.join();
+        }
+    }
+}
+
+fn upload_loop(store: impl ObjectStore, jobs: mpsc::Receiver<Job>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            log::error!("Failed to start s3 upload runtime: {}", err);
+            return;
+        }
+    };
+    let mut failure: Option<io::Error> = None;
+    for job in jobs {
+        match job {
+            Job::Upload { key, bytes } => {
+                let result = runtime.block_on(async {
+                    let upload = store.put_multipart(&ObjectPath::from(key.as_str())).await?;
+                    let mut writer = WriteMultipart::new(upload);
+                    writer.write(&bytes);
+                    writer.finish().await
+                });
+                match result {
+                    Ok(_) => log::info!("Uploaded s3 object {} ({} bytes)", key, bytes.len()),
+                    Err(err) => {
+                        log::error!("Failed to upload s3 object {}: {}", key, err);
+                        failure.get_or_insert(io::Error::other(err));
+                    }
+                }
+            }
+            Job::Barrier(reply) => {
+                let _ = reply.send(failure.take().map_or(Ok(()), Err));
+            }
+        }
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,143 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{
+    BlockSummary, MigrationEvent, NetPosition, NewHolderEvent, OwnedTradeEvent, TransferEvent,
+};
+use crate::utils::vwap::VwapSnapshot;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Opens the sink of a mint. The flag is `true` when the mint's sink was
+/// opened before and closed to stay within the open limit, in which case the
+/// new sink must append to the earlier output rather than replace it.
+pub type MintSinkFactory = Box<dyn Fn(&str, bool) -> io::Result<Box<dyn TradeSink>> + Send + Sync>;
+
+/// Open per-mint sinks by mint, with the tick of their last use.
+#[derive(Default)]
+struct OpenSinks {
+    sinks: HashMap<String, (Box<dyn TradeSink>, u64)>,
+    /// Mints whose sink was closed to make room for another.
+    closed: HashSet<String>,
+    tick: u64,
+}
+
+/// Routes every event to a sink of its own mint, so each mint's events end up
+/// in a separate output.
+///
+/// Sinks are opened on a mint's first event. At most `max_open` stay open;
+/// beyond that the least recently used one is flushed and closed, and reopened
+/// in append mode on the mint's next event, which bounds open file handles
+/// when many mints are tracked, for example by several plugins sharing this
+/// sink. Breadth snapshots and undecoded instructions have no mint and are
+/// not written.
+pub struct SplitByMintSink {
+    open: MintSinkFactory,
+    max_open: usize,
+    sinks: Mutex<OpenSinks>,
+}
+
+impl SplitByMintSink {
+    /// Creates a sink opening per-mint sinks with `open`, keeping at most
+    /// `max_open` (at least one) open at a time.
+    pub fn new(open: MintSinkFactory, max_open: usize) -> Self {
+        Self {
+            open,
+            max_open: max_open.max(1),
+            sinks: Mutex::default(),
+        }
+    }
+
+    fn with_sink(
+        &self,
+        mint: &str,
+        write: impl FnOnce(&dyn TradeSink) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut open = self
+            .sinks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        open.tick += 1;
+        let tick = open.tick;
+        if let Some((sink, last_used)) = open.sinks.get_mut(mint) {
+            *last_used = tick;
+            return write(sink.as_ref());
+        }
+
+        if open.sinks.len() >= self.max_open {
+            let lru = open
+                .sinks
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(mint, _)| mint.clone());
+            if let Some(lru) = lru {
+                if let Some((sink, _)) = open.sinks.remove(&lru) {
+                    sink.flush()?;
+                }
+                open.closed.insert(lru);
+            }
+        }
+        let reopen = open.closed.remove(mint);
+        let sink = (self.open)(mint, reopen)?;
+        let result = write(sink.as_ref());
+        open.sinks.insert(mint.to_string(), (sink, tick));
+        result
+    }
+}
+
+impl TradeSink for SplitByMintSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        self.with_sink(&event.mint, |sink| sink.write_event(event))
+    }
+
+    fn write_migration(&self, event: &MigrationEvent) -> io::Result<()> {
+        self.with_sink(&event.mint, |sink| sink.write_migration(event))
+    }
+
+    fn write_new_holder(&self, event: &NewHolderEvent) -> io::Result<()> {
+        self.with_sink(&event.mint, |sink| sink.write_new_holder(event))
+    }
+
+    fn write_transfer(&self, event: &TransferEvent) -> io::Result<()> {
+        self.with_sink(&event.mint, |sink| sink.write_transfer(event))
+    }
+
+    fn write_block_summary(&self, summary: &BlockSummary) -> io::Result<()> {
+        self.with_sink(&summary.mint, |sink| sink.write_block_summary(summary))
+    }
+
+    fn write_net_position(&self, position: &NetPosition) -> io::Result<()> {
+        self.with_sink(&position.mint, |sink| sink.write_net_position(position))
+    }
+
+    fn write_vwap(&self, snapshot: &VwapSnapshot) -> io::Result<()> {
+        self.with_sink(&snapshot.mint, |sink| sink.write_vwap(snapshot))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let open = self
+            .sinks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (sink, _) in open.sinks.values() {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the output path of `mint` for the base path `base`:
+/// `<stem>.<mint>.<ext>` next to it.
+pub fn mint_path(base: &Path, mint: &str) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match base.extension() {
+        Some(ext) => format!("{stem}.{mint}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{mint}"),
+    };
+    base.with_file_name(name)
+}
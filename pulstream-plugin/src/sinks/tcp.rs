@@ -0,0 +1,308 @@
+use super::TradeSink;
+use crate::plugins::pumpfun_tracking::{ControlEvent, OwnedTradeEvent};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// First delay before reconnecting to the collector, doubled after every
+/// failed attempt up to [`MAX_BACKOFF`].
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct State {
+    /// Serialized events waiting to be sent, each ending in a newline.
+    queue: VecDeque<Vec<u8>>,
+    /// Whether the writer holds a batch taken from the queue.
+    in_flight: bool,
+    connected: bool,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Streams each trade event as a line of JSON over a TCP connection to a
+/// collector. Run markers are sent as lines too, told apart from trades by
+/// their `type` field (see [`ControlEvent`]).
+///
+/// Events are queued and sent by a background thread, so a slow or
+/// unreachable collector never blocks the firehose. When the connection
+/// drops, the thread reconnects with exponential backoff while events keep
+/// queueing; once `capacity` events are queued, further events are dropped
+/// and counted (see [`Self::dropped`]). A batch that failed mid-write is sent
+/// again in full after reconnecting, so the collector may see a few events
+/// twice. TCP only reports a closed connection on the write after the one
+/// that went unread, so the events written just before the collector went
+/// away can also be lost.
+pub struct TcpSink {
+    addr: String,
+    capacity: usize,
+    shared: Arc<Shared>,
+    dropped: AtomicU64,
+    /// Drops already reported by `flush`.
+    reported: AtomicU64,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl TcpSink {
+    /// Connects to the collector at `addr` (`host:port`), queueing up to
+    /// `capacity` events while it is unreachable. Fails if the first
+    /// connection cannot be made.
+    pub fn connect(addr: &str, capacity: usize) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                connected: true,
+                ..State::default()
+            }),
+            changed: Condvar::new(),
+        });
+        let writer = {
+            let addr = addr.to_string();
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("pulstream-tcp-sink".to_string())
+                .spawn(move || write_loop(&addr, stream, &shared))?
+        };
+        Ok(Self {
+            addr: addr.to_string(),
+            capacity: capacity.max(1),
+            shared,
+            dropped: AtomicU64::new(0),
+            reported: AtomicU64::new(0),
+            writer: Some(writer),
+        })
+    }
+
+    /// Returns the number of events dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn enqueue(&self, record: &impl Serialize) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record).map_err(io::Error::other)?;
+        line.push(b'\n');
+        let mut state = self.shared.lock();
+        if state.queue.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        state.queue.push_back(line);
+        self.shared.changed.notify_all();
+        Ok(())
+    }
+}
+
+impl TradeSink for TcpSink {
+    fn write_event(&self, event: &OwnedTradeEvent) -> io::Result<()> {
+        self.enqueue(event)
+    }
+
+    fn write_control(&self, event: &ControlEvent) -> io::Result<()> {
+        self.enqueue(event)
+    }
+
+    /// Waits until every queued event has been sent. Fails without waiting
+    /// while the collector is unreachable.
+    fn flush(&self) -> io::Result<()> {
+        let dropped = self.dropped();
+        let reported = self.reported.swap(dropped, Ordering::Relaxed);
+        if dropped > reported {
+            warn!(
+                "TCP sink dropped {} event(s) for {} while its queue was full ({} in total)",
+                dropped - reported,
+                self.addr,
+                dropped
+            );
+        }
+        let mut state = self.shared.lock();
+        loop {
+            if state.queue.is_empty() && !state.in_flight {
+                return Ok(());
+            }
+            if !state.connected {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    format!(
+                        "collector {} is unreachable with {} event(s) queued",
+                        self.addr,
+                        state.queue.len()
+                    ),
+                ));
+            }
+            state = self
+                .shared
+                .changed
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+impl Drop for TcpSink {
+    /// Stops the writer once the queue is sent, or right away if the
+    /// collector is unreachable.
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.changed.notify_all();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn write_loop(addr: &str, stream: TcpStream, shared: &Shared) {
+    let mut stream = Some(stream);
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let batch: Vec<Vec<u8>> = {
+            let mut state = shared.lock();
+            while state.queue.is_empty() && !state.shutdown {
+                state = shared
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            if state.queue.is_empty() {
+                return;
+            }
+            state.in_flight = true;
+            state.queue.drain(..).collect()
+        };
+
+        let connection = match stream.as_mut() {
+            Some(connection) => Ok(connection),
+            None => TcpStream::connect(addr)
+                .and_then(|connection| {
+                    connection.set_nodelay(true)?;
+                    Ok(connection)
+                })
+                .map(|connection| {
+                    info!("Reconnected to collector {}", addr);
+                    backoff = MIN_BACKOFF;
+                    stream.insert(connection)
+                }),
+        };
+        let sent = connection.and_then(|connection| {
+            shared.lock().connected = true;
+            connection.write_all(&batch.concat())?;
+            connection.flush()
+        });
+
+        let mut state = shared.lock();
+        state.in_flight = false;
+        match sent {
+            Ok(()) => {
+                shared.changed.notify_all();
+                continue;
+            }
+            Err(err) => {
+                if state.connected {
+                    warn!("Lost connection to collector {}: {}", addr, err);
+                }
+                stream = None;
+                state.connected = false;
+                for line in batch.into_iter().rev() {
+                    state.queue.push_front(line);
+                }
+                shared.changed.notify_all();
+            }
+        }
+
+        // Wait out the backoff, waking early only to shut down.
+        let deadline = Instant::now() + backoff;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        while !state.shutdown {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = shared
+                .changed
+                .wait_timeout(state, deadline - now)
+                .map(|(state, _)| state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+        }
+        if state.shutdown {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::pumpfun_tracking::PumpfunTrackingPlugin;
+    use crate::utils::self_test::{fixtures, FIXTURE_MINT};
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+
+    fn events() -> Vec<OwnedTradeEvent> {
+        let plugin = PumpfunTrackingPlugin::new(FIXTURE_MINT);
+        fixtures()
+            .iter()
+            .flat_map(|fixture| plugin.process_transaction(&fixture.transaction).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn sends_json_lines_and_drops_once_full_while_disconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let sink = TcpSink::connect(&addr, 2).unwrap();
+        let (collector, _) = listener.accept().unwrap();
+
+        let events = events();
+        for event in &events {
+            sink.write_event(event).unwrap();
+        }
+        sink.flush().unwrap();
+        let mut lines = BufReader::new(collector.try_clone().unwrap()).lines();
+        for event in &events {
+            let line = lines.next().unwrap().unwrap();
+            let received: OwnedTradeEvent = serde_json::from_str(&line).unwrap();
+            assert_eq!(&received, event);
+        }
+        assert_eq!(sink.dropped(), 0);
+
+        // With the collector gone the writer notices on a later write, stops
+        // draining the queue and the sink drops what does not fit.
+        drop(lines);
+        drop(collector);
+        drop(listener);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while sink.dropped() == 0 {
+            assert!(Instant::now() < deadline, "no event was dropped");
+            sink.write_event(&events[0]).unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            sink.flush().unwrap_err().kind(),
+            io::ErrorKind::NotConnected
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Helpers for reading token balance changes out of transaction status
+//! metadata.
+//!
+//! The runtime records `pre_token_balances` and `post_token_balances` for every
+//! token account touched by a transaction. Comparing the two for a given
+//! owner and mint yields the net amount of that token the owner gained or lost,
+//! which can be checked against what a decoder attributed to the trade.
+
+use solana_transaction_status::{TransactionStatusMeta, TransactionTokenBalance};
+
+/// Returns the recorded balance of the token account at `account_index` in
+/// the transaction's account keys, preferring the post balance since the
+/// account may have been created by the transaction, and the pre balance if
+/// it was closed.
+///
+/// Returns `None` when the account is not a token account or the metadata
+/// carries no token balances.
+pub fn token_account_balance(
+    meta: &TransactionStatusMeta,
+    account_index: usize,
+) -> Option<&TransactionTokenBalance> {
+    find_balance(meta.post_token_balances.as_deref(), account_index)
+        .or_else(|| find_balance(meta.pre_token_balances.as_deref(), account_index))
+}
+
+fn find_balance(
+    balances: Option<&[TransactionTokenBalance]>,
+    account_index: usize,
+) -> Option<&TransactionTokenBalance> {
+    balances?
+        .iter()
+        .find(|balance| usize::from(balance.account_index) == account_index)
+}
+
+/// Returns the net change in raw token units of `mint` held by `owner`
+/// across the transaction.
+///
+/// Returns `None` when the metadata carries no token balances at all, which is
+/// the case for old blocks recorded before balances were tracked.
+pub fn owner_token_delta(meta: &TransactionStatusMeta, mint: &str, owner: &str) -> Option<i128> {
+    let pre = meta.pre_token_balances.as_deref()?;
+    let post = meta.post_token_balances.as_deref()?;
+    Some(owned_amount(post, mint, owner) - owned_amount(pre, mint, owner))
+}
+
+fn owned_amount(balances: &[TransactionTokenBalance], mint: &str, owner: &str) -> i128 {
+    balances
+        .iter()
+        .filter(|balance| balance.mint == mint && balance.owner == owner)
+        .filter_map(|balance| balance.ui_token_amount.amount.parse::<i128>().ok())
+        .sum()
+}
+
+/// Returns whether `owner` held no token account of `mint` before the
+/// transaction but does afterwards.
+///
+/// Returns `None` when the metadata carries no token balances.
+pub fn gained_token_account(meta: &TransactionStatusMeta, mint: &str, owner: &str) -> Option<bool> {
+    let pre = meta.pre_token_balances.as_deref()?;
+    let post = meta.post_token_balances.as_deref()?;
+    let holds = |balances: &[TransactionTokenBalance]| {
+        balances
+            .iter()
+            .any(|balance| balance.mint == mint && balance.owner == owner)
+    };
+    Some(!holds(pre) && holds(post))
+}
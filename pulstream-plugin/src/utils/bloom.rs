@@ -0,0 +1,108 @@
+//! Membership tests against large sets of keys, such as fee payer allow lists
+//! or excluded programs with thousands of entries.
+//!
+//! Every account key of every transaction is looked up, and almost all of
+//! them are not in the set. A [`KeySet`] above [`BLOOM_THRESHOLD`] keys puts a
+//! Bloom filter in front of its `HashSet`: a key the filter rules out is
+//! rejected after a few bit tests without hashing it, and only possible
+//! members are confirmed against the exact set, so there are no false
+//! positives. Smaller sets use the `HashSet` alone.
+//!
+//! The filter derives its bit positions straight from the key bytes instead
+//! of hashing them, since public keys are already uniformly distributed. Keys
+//! that are not, such as the all-zero system program, only cost a few extra
+//! exact lookups.
+
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+
+/// Sets with more keys than this are prefiltered with a Bloom filter.
+pub const BLOOM_THRESHOLD: usize = 1024;
+
+/// Bits per key, which with [`HASHES`] probes gives a false positive rate of
+/// about 1%.
+const BITS_PER_KEY: usize = 10;
+const HASHES: u64 = 7;
+
+/// Bloom filter over 32-byte keys.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    /// Number of bits minus one; the number of bits is a power of two.
+    mask: u64,
+}
+
+impl BloomFilter {
+    /// Creates an empty filter sized for `keys` keys.
+    pub fn with_capacity(keys: usize) -> Self {
+        let bits = (keys.max(1) * BITS_PER_KEY).next_power_of_two().max(64);
+        Self {
+            bits: vec![0; bits / 64],
+            mask: bits as u64 - 1,
+        }
+    }
+
+    /// Adds `key`.
+    pub fn insert(&mut self, key: &[u8; 32]) {
+        for bit in positions(key, self.mask) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns `false` if `key` was never inserted; `true` means it may have
+    /// been.
+    pub fn might_contain(&self, key: &[u8; 32]) -> bool {
+        positions(key, self.mask).all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+/// Bit positions of `key`, by double hashing from two words of the key.
+fn positions(key: &[u8; 32], mask: u64) -> impl Iterator<Item = u64> {
+    let word = |at: usize| u64::from_le_bytes(key[at..at + 8].try_into().expect("8 byte slice"));
+    let h1 = word(0) ^ word(16);
+    let h2 = (word(8) ^ word(24)) | 1;
+    (0..HASHES).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) & mask)
+}
+
+/// Set of public keys with exact membership, prefiltered by a Bloom filter
+/// when large.
+#[derive(Debug, Clone)]
+pub struct KeySet {
+    keys: HashSet<Pubkey>,
+    bloom: Option<BloomFilter>,
+}
+
+impl KeySet {
+    /// Builds the set, adding a Bloom filter if it holds more than
+    /// [`BLOOM_THRESHOLD`] keys.
+    pub fn new(keys: HashSet<Pubkey>) -> Self {
+        let bloom = (keys.len() > BLOOM_THRESHOLD).then(|| {
+            let mut bloom = BloomFilter::with_capacity(keys.len());
+            for key in &keys {
+                bloom.insert(&key.to_bytes());
+            }
+            bloom
+        });
+        Self { keys, bloom }
+    }
+
+    /// Returns whether `key` is in the set.
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        if let Some(bloom) = &self.bloom {
+            if !bloom.might_contain(&key.to_bytes()) {
+                return false;
+            }
+        }
+        self.keys.contains(key)
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
@@ -0,0 +1,100 @@
+//! Market breadth: the number of distinct mints traded per window of slots.
+//!
+//! Windows are aligned to multiples of their length in slots. Mints are
+//! recorded per window as trades arrive, and a window closes once a block of a
+//! later window is seen, or at exit. Windows in which blocks were seen but
+//! nothing traded close with zero mints; windows without any block, such as
+//! gaps between slot ranges, produce no snapshot.
+//!
+//! Closing on blocks assumes slots arrive in order. With several threads,
+//! a trade can arrive after its window has closed; such trades are counted as
+//! late and otherwise ignored.
+
+use std::collections::{BTreeMap, HashSet};
+
+/// Distinct mints traded within one window of slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreadthSnapshot {
+    /// First slot of the window, inclusive.
+    pub window_start_slot: u64,
+    /// Last slot of the window, exclusive.
+    pub window_end_slot: u64,
+    pub unique_mints: u64,
+}
+
+/// Open breadth windows, keyed by their first slot.
+#[derive(Debug)]
+pub struct BreadthWindows {
+    length: u64,
+    open: BTreeMap<u64, HashSet<[u8; 32]>>,
+    /// Windows starting below this slot have been closed.
+    closed_below: u64,
+    late: u64,
+}
+
+impl BreadthWindows {
+    /// Creates windows of `length_slots` slots (at least one).
+    pub fn new(length_slots: u64) -> Self {
+        Self {
+            length: length_slots.max(1),
+            open: BTreeMap::new(),
+            closed_below: 0,
+            late: 0,
+        }
+    }
+
+    /// Records a trade of `mint` in `slot`.
+    pub fn record(&mut self, slot: u64, mint: [u8; 32]) {
+        let start = self.window_start(slot);
+        if start < self.closed_below {
+            self.late += 1;
+            return;
+        }
+        self.open.entry(start).or_default().insert(mint);
+    }
+
+    /// Marks `slot` as seen, returning the snapshots of the windows before it,
+    /// which it closes.
+    pub fn on_slot(&mut self, slot: u64) -> Vec<BreadthSnapshot> {
+        let start = self.window_start(slot);
+        if start < self.closed_below {
+            return Vec::new();
+        }
+        let current = self.open.split_off(&start);
+        let closed = std::mem::replace(&mut self.open, current);
+        self.open.entry(start).or_default();
+        self.closed_below = start;
+        closed
+            .into_iter()
+            .map(|(start, mints)| self.snapshot(start, &mints))
+            .collect()
+    }
+
+    /// Closes every open window.
+    pub fn drain(&mut self) -> Vec<BreadthSnapshot> {
+        let open = std::mem::take(&mut self.open);
+        if let Some(last) = open.keys().next_back() {
+            self.closed_below = last.saturating_add(self.length);
+        }
+        open.into_iter()
+            .map(|(start, mints)| self.snapshot(start, &mints))
+            .collect()
+    }
+
+    /// Returns the number of trades that arrived after their window closed.
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    fn window_start(&self, slot: u64) -> u64 {
+        slot - slot % self.length
+    }
+
+    fn snapshot(&self, start: u64, mints: &HashSet<[u8; 32]>) -> BreadthSnapshot {
+        BreadthSnapshot {
+            window_start_slot: start,
+            window_end_slot: start.saturating_add(self.length),
+            unique_mints: mints.len() as u64,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Self-contained snapshots of a single firehose transaction.
+//!
+//! A bundle holds everything the plugin reads from a [`TransactionData`]: the
+//! slot, signature, full transaction and status metadata. It is written with
+//! bincode, so a misdecoded transaction can be exported during a run and fed
+//! back through a plugin's `on_transaction` hook elsewhere.
+//!
+//! Status metadata is stored in the ledger's bincode form
+//! ([`StoredTransactionStatusMeta`]), which has no room for the addresses
+//! loaded through lookup tables, so those are stored alongside it.
+
+use jetstreamer::{firehose::firehose::TransactionData, plugin::Plugin};
+use serde::{Deserialize, Serialize};
+use solana_hash::Hash;
+use solana_message::v0::LoadedAddresses;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_storage_proto::StoredTransactionStatusMeta;
+use solana_transaction::versioned::VersionedTransaction;
+use std::{
+    error::Error,
+    io::{self, Read, Write},
+};
+
+/// Serializable copy of a [`TransactionData`].
+#[derive(Serialize, Deserialize)]
+pub struct TransactionBundle {
+    slot: u64,
+    transaction_slot_index: usize,
+    signature: Vec<u8>,
+    message_hash: [u8; 32],
+    is_vote: bool,
+    transaction: VersionedTransaction,
+    meta: StoredTransactionStatusMeta,
+    loaded_writable: Vec<[u8; 32]>,
+    loaded_readonly: Vec<[u8; 32]>,
+}
+
+impl TransactionBundle {
+    /// Captures `transaction`.
+    pub fn from_transaction(transaction: &TransactionData) -> io::Result<Self> {
+        let mut meta = transaction.transaction_status_meta.clone();
+        let loaded = std::mem::take(&mut meta.loaded_addresses);
+        Ok(Self {
+            slot: transaction.slot,
+            transaction_slot_index: transaction.transaction_slot_index,
+            signature: transaction.signature.as_ref().to_vec(),
+            message_hash: transaction.message_hash.to_bytes(),
+            is_vote: transaction.is_vote,
+            transaction: transaction.transaction.clone(),
+            meta: StoredTransactionStatusMeta::try_from(meta).map_err(io::Error::other)?,
+            loaded_writable: loaded.writable.into_iter().map(Pubkey::to_bytes).collect(),
+            loaded_readonly: loaded.readonly.into_iter().map(Pubkey::to_bytes).collect(),
+        })
+    }
+
+    /// Rebuilds the captured transaction.
+    pub fn into_transaction(self) -> io::Result<TransactionData> {
+        let signature = Signature::try_from(self.signature.as_slice())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut meta: solana_transaction_status::TransactionStatusMeta = self.meta.into();
+        meta.loaded_addresses = LoadedAddresses {
+            writable: self
+                .loaded_writable
+                .into_iter()
+                .map(Pubkey::new_from_array)
+                .collect(),
+            readonly: self
+                .loaded_readonly
+                .into_iter()
+                .map(Pubkey::new_from_array)
+                .collect(),
+        };
+        Ok(TransactionData {
+            slot: self.slot,
+            transaction_slot_index: self.transaction_slot_index,
+            signature,
+            message_hash: Hash::new_from_array(self.message_hash),
+            is_vote: self.is_vote,
+            transaction_status_meta: meta,
+            transaction: self.transaction,
+        })
+    }
+
+    /// Writes the bundle to `writer`.
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        bincode::serialize_into(writer, self).map_err(io::Error::other)
+    }
+
+    /// Reads a bundle written by [`Self::write_to`].
+    pub fn read_from(reader: impl Read) -> io::Result<Self> {
+        bincode::deserialize_from(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Feeds `transaction` through `plugin` as a one-transaction run: `on_load`,
+/// `on_transaction` and `on_exit`, on a fresh runtime and without ClickHouse.
+pub fn replay_transaction(
+    plugin: &dyn Plugin,
+    transaction: &TransactionData,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        plugin.on_load(None).await?;
+        plugin.on_transaction(0, None, transaction).await?;
+        plugin.on_exit(None).await
+    })
+}
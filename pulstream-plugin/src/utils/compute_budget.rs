@@ -0,0 +1,41 @@
+//! Minimal parsing of compute-budget program instructions.
+//!
+//! Only `SetComputeUnitPrice` is read. Its data is a one-byte discriminator
+//! (`3`) followed by the price in micro-lamports per compute unit as a
+//! little-endian `u64`, nine bytes in total. The runtime only honours
+//! compute-budget instructions at the top level of a transaction, and uses the
+//! last one if several are present.
+
+use solana_message::VersionedMessage;
+use solana_pubkey::{pubkey, Pubkey};
+
+/// Address of the compute-budget program.
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Discriminator of `ComputeBudgetInstruction::SetComputeUnitPrice`.
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Returns the compute unit price set by `message`, in micro-lamports, or
+/// `None` if it sets none.
+pub fn compute_unit_price(message: &VersionedMessage) -> Option<u64> {
+    let account_keys = message.static_account_keys();
+    message
+        .instructions()
+        .iter()
+        .rev()
+        .filter(|instruction| {
+            account_keys.get(instruction.program_id_index as usize)
+                == Some(&COMPUTE_BUDGET_PROGRAM_ID)
+        })
+        .find_map(|instruction| parse_set_compute_unit_price(&instruction.data))
+}
+
+fn parse_set_compute_unit_price(data: &[u8]) -> Option<u64> {
+    match data {
+        [SET_COMPUTE_UNIT_PRICE, price @ ..] if price.len() == 8 => {
+            Some(u64::from_le_bytes(price.try_into().ok()?))
+        }
+        _ => None,
+    }
+}
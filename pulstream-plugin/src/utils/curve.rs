@@ -0,0 +1,79 @@
+//! Progress of a pumpfun bonding curve towards graduation.
+//!
+//! Every pumpfun curve starts with the same reserves from the program's global
+//! config: 1,073,000,000 virtual tokens, of which 793,100,000 are real tokens
+//! for sale (both with 6 decimals), against 30 virtual SOL. Buying moves real
+//! tokens out of the curve, and the curve completes, migrating its liquidity
+//! to an AMM pool, once the real token reserves reach zero, that is once the
+//! virtual token reserves have fallen to the 279,900,000 tokens that were
+//! never for sale. Progress is therefore the share of the sellable tokens
+//! sold:
+//!
+//! ```text
+//! progress = (initial_virtual_tokens - virtual_tokens) / initial_real_tokens
+//! ```
+//!
+//! The token side is used rather than SOL because the SOL raised at graduation
+//! depends on the fees and price path, while the token supply is fixed.
+
+/// Virtual token reserves of a new curve, in raw units.
+pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+
+/// Real token reserves of a new curve, the tokens sold before graduation, in
+/// raw units.
+pub const INITIAL_REAL_TOKEN_RESERVES: u64 = 793_100_000_000_000;
+
+/// Returns how far a curve with `virtual_token_reserves` is towards
+/// graduation, from `0.0` for a new curve to `1.0` for a completed one.
+/// Reserves outside the expected range, such as those of a curve created
+/// under different global parameters, are clamped.
+///
+/// Returns `None` for zero virtual token reserves, which no curve reaches
+/// since the virtual part is never sold: the event carried no reserves.
+pub fn curve_progress(virtual_token_reserves: u64) -> Option<f64> {
+    if virtual_token_reserves == 0 {
+        return None;
+    }
+    let sold = INITIAL_VIRTUAL_TOKEN_RESERVES.saturating_sub(virtual_token_reserves);
+    Some((sold as f64 / INITIAL_REAL_TOKEN_RESERVES as f64).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Virtual token reserves once `sold` of the sellable tokens are sold.
+    fn reserves_after(sold: u64) -> u64 {
+        INITIAL_VIRTUAL_TOKEN_RESERVES - sold
+    }
+
+    #[test]
+    fn progress_at_start_middle_and_end() {
+        assert_eq!(curve_progress(INITIAL_VIRTUAL_TOKEN_RESERVES), Some(0.0));
+        assert_eq!(
+            curve_progress(reserves_after(INITIAL_REAL_TOKEN_RESERVES / 2)),
+            Some(0.5)
+        );
+        let almost =
+            curve_progress(reserves_after(INITIAL_REAL_TOKEN_RESERVES - 1_000_000)).unwrap();
+        assert!(almost > 0.999 && almost < 1.0, "{almost}");
+        assert_eq!(
+            curve_progress(reserves_after(INITIAL_REAL_TOKEN_RESERVES)),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn clamps_reserves_outside_the_curve() {
+        assert_eq!(
+            curve_progress(INITIAL_VIRTUAL_TOKEN_RESERVES * 2),
+            Some(0.0)
+        );
+        assert_eq!(curve_progress(1), Some(1.0));
+    }
+
+    #[test]
+    fn no_progress_without_reserves() {
+        assert_eq!(curve_progress(0), None);
+    }
+}
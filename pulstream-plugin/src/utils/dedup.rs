@@ -0,0 +1,135 @@
+//! On-disk set of emitted events, keyed by `(signature, instruction_path)`,
+//! so a resumed run does not emit events a previous run already did.
+//!
+//! The store is a text file with one `<slot> <signature> <instruction_path>`
+//! line per emitted event. New entries are buffered in memory and appended
+//! only by [`DedupStore::flush`], which the plugin calls after flushing its
+//! sinks, so the store never outlives the output it vouches for: a crash
+//! loses at most events that are then emitted again. Opening it prunes
+//! every entry below a given slot, normally the first slot of the new run,
+//! since the run cannot produce those events again. The store thus stays
+//! bounded by the events of the slots runs have in common.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Persistent set of emitted event keys.
+#[derive(Debug)]
+pub struct DedupStore {
+    path: PathBuf,
+    seen: HashSet<(String, String)>,
+    file: File,
+    /// Entries inserted since the last flush.
+    pending: String,
+}
+
+impl DedupStore {
+    /// Opens the store at `path`, creating it if needed and dropping entries
+    /// of slots below `prune_below`. The pruned store is written to a
+    /// temporary file and renamed into place.
+    pub fn open(path: impl Into<PathBuf>, prune_below: u64) -> io::Result<Self> {
+        let path = path.into();
+        let mut kept = Vec::new();
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    let mut fields = line.split(' ');
+                    let (Some(slot), Some(signature), Some(instruction_path), None) =
+                        (fields.next(), fields.next(), fields.next(), fields.next())
+                    else {
+                        // A torn last line from an interrupted run.
+                        continue;
+                    };
+                    let Ok(slot) = slot.parse::<u64>() else {
+                        continue;
+                    };
+                    if slot >= prune_below {
+                        kept.push((slot, signature.to_string(), instruction_path.to_string()));
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        let tmp = tmp_path(&path);
+        let mut compacted = BufWriter::new(File::create(&tmp)?);
+        for (slot, signature, instruction_path) in &kept {
+            writeln!(compacted, "{slot} {signature} {instruction_path}")?;
+        }
+        compacted
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+        let seen = kept
+            .into_iter()
+            .map(|(_, signature, instruction_path)| (signature, instruction_path))
+            .collect();
+        Ok(Self {
+            path,
+            seen,
+            file,
+            pending: String::new(),
+        })
+    }
+
+    /// Returns whether the event is in the store.
+    pub fn contains(&self, signature: &str, instruction_path: &str) -> bool {
+        self.seen
+            .contains(&(signature.to_string(), instruction_path.to_string()))
+    }
+
+    /// Records an event, returning `false` if it was already in the store.
+    /// The entry reaches disk on the next [`Self::flush`].
+    pub fn insert(&mut self, slot: u64, signature: &str, instruction_path: &str) -> bool {
+        let key = (signature.to_string(), instruction_path.to_string());
+        if self.seen.contains(&key) {
+            return false;
+        }
+        self.pending
+            .push_str(&format!("{slot} {signature} {instruction_path}\n"));
+        self.seen.insert(key);
+        true
+    }
+
+    /// Returns the size in bytes of the entries not yet on disk.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Appends the entries inserted since the last flush to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.write_all(self.pending.as_bytes())?;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Returns the number of events in the store.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Returns whether the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Returns the path of the store.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
@@ -0,0 +1,90 @@
+//! Summary of the errors a run recovered from, grouped by kind, logged at
+//! exit so a long `--skip-errors` run ends with one readable list instead of
+//! warnings scattered through its output.
+//!
+//! Each kind is logged with its count and its first few examples:
+//!
+//! ```text
+//! Recovered from 1204 error(s):
+//!   decode failures: 1201
+//!     slot 250000123 5h3k...: failed to decode instruction 2.0 of 5h3k...
+//!     ...and 1198 more
+//!   decoder panics: 3
+//! ```
+//!
+//! Kind names are colored when stderr is a terminal, unless `NO_COLOR` is set.
+
+use log::warn;
+use std::io::{self, IsTerminal};
+
+/// Examples logged per kind; the rest are only counted.
+pub const MAX_EXAMPLES: usize = 3;
+
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone)]
+struct Section {
+    kind: String,
+    count: u64,
+    examples: Vec<String>,
+}
+
+/// Errors of a run, grouped by kind.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorReport {
+    sections: Vec<Section>,
+}
+
+impl ErrorReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `count` errors of `kind`, with examples of them, of which the
+    /// first [`MAX_EXAMPLES`] are kept. Kinds with a zero count are left out.
+    pub fn add(&mut self, kind: &str, count: u64, examples: impl IntoIterator<Item = String>) {
+        if count == 0 {
+            return;
+        }
+        self.sections.push(Section {
+            kind: kind.to_string(),
+            count,
+            examples: examples.into_iter().take(MAX_EXAMPLES).collect(),
+        });
+    }
+
+    /// Returns the number of errors over every kind.
+    pub fn total(&self) -> u64 {
+        self.sections.iter().map(|section| section.count).sum()
+    }
+
+    /// Returns whether no errors were added.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Logs the report as warnings. Logs nothing if it is empty.
+    pub fn log(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let color = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        warn!("Recovered from {} error(s):", self.total());
+        for section in &self.sections {
+            if color {
+                warn!("  {}{}{}: {}", RED, section.kind, RESET, section.count);
+            } else {
+                warn!("  {}: {}", section.kind, section.count);
+            }
+            for example in &section.examples {
+                warn!("    {}", example);
+            }
+            let unlisted = section.count.saturating_sub(section.examples.len() as u64);
+            if !section.examples.is_empty() && unlisted > 0 {
+                warn!("    ...and {} more", unlisted);
+            }
+        }
+    }
+}
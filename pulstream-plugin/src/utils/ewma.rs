@@ -0,0 +1,64 @@
+//! Exponentially time-weighted moving average of trade prices.
+//!
+//! Each trade moves the average towards its price by
+//! `alpha = 1 - 2^(-dt / half_life)`, where `dt` is the time since the previous
+//! trade of the same mint:
+//!
+//! ```text
+//! ewma = ewma + alpha * (price - ewma)
+//! ```
+//!
+//! A price observed one half-life ago therefore carries half the weight of
+//! the current one. Trade timestamps have one-second resolution, so `dt` is at
+//! least one second; trades within the same second, or arriving out of order
+//! across threads, each count as one second apart.
+
+use std::{collections::HashMap, time::Duration};
+
+#[derive(Debug, Clone, Copy)]
+struct State {
+    value: f64,
+    timestamp: i64,
+}
+
+/// Per-key time-decayed average.
+#[derive(Debug, Clone)]
+pub struct TimeDecayedAverage {
+    half_life_secs: f64,
+    states: HashMap<String, State>,
+}
+
+impl TimeDecayedAverage {
+    /// Creates an empty average with the given half-life.
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            half_life_secs: half_life.as_secs_f64().max(f64::MIN_POSITIVE),
+            states: HashMap::new(),
+        }
+    }
+
+    /// Folds `value` observed at `timestamp` (Unix seconds) into the average
+    /// of `key` and returns the updated average. The first value of a key
+    /// seeds it.
+    pub fn update(&mut self, key: &str, value: f64, timestamp: i64) -> f64 {
+        match self.states.get_mut(key) {
+            Some(state) => {
+                let dt = timestamp.saturating_sub(state.timestamp).max(1) as f64;
+                let alpha = 1.0 - (-dt / self.half_life_secs).exp2();
+                state.value += alpha * (value - state.value);
+                state.timestamp = state.timestamp.max(timestamp);
+                state.value
+            }
+            None => {
+                self.states
+                    .insert(key.to_string(), State { value, timestamp });
+                value
+            }
+        }
+    }
+
+    /// Returns the current average of `key`, if it has seen any value.
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.states.get(key).map(|state| state.value)
+    }
+}
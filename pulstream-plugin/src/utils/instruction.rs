@@ -28,6 +28,9 @@ pub struct TransactionMetadata {
     pub meta: TransactionStatusMeta,
     /// The versioned message containing instructions and account keys.
     pub message: VersionedMessage,
+    /// Unix timestamp of the block, as recorded by the cluster. `None` when
+    /// unknown; the firehose reports a block only after all its transactions.
+    pub block_time: Option<i64>,
 }
 
 /// Instruction metadata containing transaction metadata, stack height, index, and absolute path.
@@ -43,6 +46,19 @@ pub struct InstructionMetadata {
     pub absolute_path: Vec<u8>,
 }
 
+impl InstructionMetadata {
+    /// Renders `absolute_path` as a dotted string, e.g. `"2.0.1"` for the second
+    /// instruction invoked by the first inner instruction of top-level
+    /// instruction 2. Unique within a transaction.
+    pub fn instruction_path(&self) -> String {
+        self.absolute_path
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
 /// TD
 pub type InstructionsWithMetadata = Vec<(InstructionMetadata, Instruction)>;
 
@@ -69,11 +85,66 @@ pub struct NestedInstruction {
     pub inner_instructions: NestedInstructions,
 }
 
+impl NestedInstruction {
+    /// Depth of the tree rooted at this instruction; 1 if it invoked nothing.
+    pub fn depth(&self) -> usize {
+        1 + self.inner_instructions.max_depth()
+    }
+
+    /// Number of instructions in the tree rooted at this instruction,
+    /// including itself.
+    pub fn total_count(&self) -> usize {
+        1 + self.inner_instructions.total_count()
+    }
+}
+
 /// TD
 #[derive(Debug, Default)]
 pub struct NestedInstructions(pub Vec<NestedInstruction>);
 
 impl NestedInstructions {
+    /// Maximum depth over all instruction trees; 0 when empty.
+    pub fn max_depth(&self) -> usize {
+        self.0
+            .iter()
+            .map(NestedInstruction::depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Total number of instructions at every level.
+    pub fn total_count(&self) -> usize {
+        self.0.iter().map(NestedInstruction::total_count).sum()
+    }
+
+    /// Nests `instructions` like the `From` impl, after checking that their
+    /// stack heights describe a tree: the first instruction is top-level,
+    /// every height lies within `1..=MAX_INSTRUCTION_STACK_DEPTH`, and no
+    /// instruction is more than one level deeper than the one before it.
+    /// The `From` impl does not check, and silently drops or misplaces
+    /// instructions whose heights break these rules.
+    pub fn from_flat(instructions: InstructionsWithMetadata) -> Result<Self, NestingError> {
+        let mut previous = 0;
+        for (index, (metadata, _)) in instructions.iter().enumerate() {
+            let stack_height = metadata.stack_height;
+            if stack_height == 0 || stack_height as usize > MAX_INSTRUCTION_STACK_DEPTH {
+                return Err(NestingError::StackHeightOutOfRange {
+                    index,
+                    stack_height,
+                });
+            }
+            if stack_height > previous + 1 {
+                return Err(NestingError::IllegalJump {
+                    index,
+                    from: previous,
+                    to: stack_height,
+                });
+            }
+            previous = stack_height;
+        }
+        Ok(instructions.into())
+    }
+
     /// TD
     pub fn len(&self) -> usize {
         self.0.len()
@@ -119,6 +190,21 @@ impl IntoIterator for NestedInstructions {
     }
 }
 
+/// Why a flat instruction list could not be nested, see
+/// [`NestedInstructions::from_flat`]. `index` is the position of the offending
+/// instruction in the list.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NestingError {
+    /// The instruction's stack height is zero or deeper than the runtime
+    /// allows.
+    #[error("instruction {index} has stack height {stack_height}")]
+    StackHeightOutOfRange { index: usize, stack_height: u32 },
+    /// The instruction is more than one level deeper than the previous one
+    /// (`from` is 0 for the first instruction), so it has no parent.
+    #[error("instruction {index} jumps from stack height {from} to {to}")]
+    IllegalJump { index: usize, from: u32, to: u32 },
+}
+
 /// Nests instructions based on stack height, producing a hierarchy of
 /// `NestedInstruction`.
 ///
@@ -383,3 +469,121 @@ impl<T: Send + 'static> InstructionPipes<'_> for InstructionPipe<T> {
         &self.filters
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One instruction per stack height, each with a distinct first data byte
+    /// so the nesting can be read back.
+    fn flat(stack_heights: &[u32]) -> InstructionsWithMetadata {
+        let transaction_metadata = Arc::new(TransactionMetadata::default());
+        stack_heights
+            .iter()
+            .enumerate()
+            .map(|(index, &stack_height)| {
+                let metadata = InstructionMetadata {
+                    transaction_metadata: transaction_metadata.clone(),
+                    stack_height,
+                    index: index as u32,
+                    absolute_path: Vec::new(),
+                };
+                let instruction = Instruction {
+                    program_id: Pubkey::default(),
+                    accounts: Vec::new(),
+                    data: vec![index as u8],
+                };
+                (metadata, instruction)
+            })
+            .collect()
+    }
+
+    fn tag(nested: &NestedInstruction) -> u8 {
+        nested.instruction.data[0]
+    }
+
+    #[test]
+    fn from_flat_nests_a_valid_tree() {
+        let nested = NestedInstructions::from_flat(flat(&[1, 2, 3, 2, 1])).unwrap();
+        assert_eq!(nested.len(), 2);
+        assert_eq!(tag(&nested[0]), 0);
+        assert_eq!(tag(&nested[1]), 4);
+        let inner = &nested[0].inner_instructions;
+        assert_eq!(inner.iter().map(tag).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(tag(&inner[0].inner_instructions[0]), 2);
+        assert!(nested[1].inner_instructions.is_empty());
+    }
+
+    #[test]
+    fn from_flat_rejects_illegal_jumps() {
+        assert_eq!(
+            NestedInstructions::from_flat(flat(&[1, 3])).unwrap_err(),
+            NestingError::IllegalJump {
+                index: 1,
+                from: 1,
+                to: 3
+            }
+        );
+        assert_eq!(
+            NestedInstructions::from_flat(flat(&[2])).unwrap_err(),
+            NestingError::IllegalJump {
+                index: 0,
+                from: 0,
+                to: 2
+            }
+        );
+    }
+
+    #[test]
+    fn from_flat_rejects_stack_heights_out_of_range() {
+        assert_eq!(
+            NestedInstructions::from_flat(flat(&[1, 0])).unwrap_err(),
+            NestingError::StackHeightOutOfRange {
+                index: 1,
+                stack_height: 0
+            }
+        );
+        let too_deep = MAX_INSTRUCTION_STACK_DEPTH as u32 + 1;
+        let mut heights: Vec<u32> = (1..=too_deep).collect();
+        heights.insert(0, 1);
+        assert_eq!(
+            NestedInstructions::from_flat(flat(&heights)).unwrap_err(),
+            NestingError::StackHeightOutOfRange {
+                index: heights.len() - 1,
+                stack_height: too_deep
+            }
+        );
+    }
+
+    #[test]
+    fn depth_and_count_of_a_known_tree() {
+        // 0 ─┬─ 1 ── 2 ── 3
+        //    └─ 4
+        // 5
+        let nested = NestedInstructions::from_flat(flat(&[1, 2, 3, 4, 2, 1])).unwrap();
+        assert_eq!(nested[0].depth(), 4);
+        assert_eq!(nested[0].total_count(), 5);
+        assert_eq!(nested[1].depth(), 1);
+        assert_eq!(nested[1].total_count(), 1);
+        assert_eq!(nested.max_depth(), 4);
+        assert_eq!(nested.total_count(), 6);
+    }
+
+    #[test]
+    fn depth_and_count_of_no_instructions() {
+        let nested = NestedInstructions::default();
+        assert_eq!(nested.max_depth(), 0);
+        assert_eq!(nested.total_count(), 0);
+    }
+
+    #[test]
+    fn instruction_path_joins_the_absolute_path_with_dots() {
+        let (mut metadata, _) = flat(&[3]).remove(0);
+        metadata.absolute_path = vec![2, 0, 1];
+        assert_eq!(metadata.instruction_path(), "2.0.1");
+        metadata.absolute_path = vec![7];
+        assert_eq!(metadata.instruction_path(), "7");
+        metadata.absolute_path.clear();
+        assert_eq!(metadata.instruction_path(), "");
+    }
+}
@@ -0,0 +1,132 @@
+//! Histograms of the time between consecutive trades of a mint, for spotting
+//! rapid-fire bot trading.
+//!
+//! Trade timestamps have one-second resolution, so gaps are whole seconds and
+//! fall into power-of-two buckets: `0s`, `1s`, `2-3s`, `4-7s` and so on, with
+//! the last bucket open-ended. Trades in the same second land in `0s`.
+//!
+//! A gap is measured from the latest timestamp seen for the mint. The runner
+//! processes slots on several threads, so trades may arrive out of order; a
+//! trade older than the latest one is counted as out of order instead of
+//! contributing a gap, and the histogram is approximate whenever that count is
+//! not zero. Run with a single thread for exact results.
+
+use std::{collections::HashMap, fmt};
+
+/// Number of buckets; the last one holds every gap of `2^(BUCKETS - 2)`
+/// seconds or more (about 18 hours).
+const BUCKETS: usize = 18;
+
+/// Inter-arrival histogram of one mint.
+#[derive(Debug, Clone, Default)]
+pub struct InterArrivalHistogram {
+    last_timestamp: Option<i64>,
+    buckets: [u64; BUCKETS],
+    out_of_order: u64,
+}
+
+impl InterArrivalHistogram {
+    /// Returns `(lower bound in seconds, count)` for every bucket. Bucket `i`
+    /// covers gaps up to the next bucket's lower bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(index, count)| (lower_bound(index), *count))
+    }
+
+    /// Returns the number of gaps recorded.
+    pub fn gaps(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the number of trades older than the latest one seen.
+    pub fn out_of_order(&self) -> u64 {
+        self.out_of_order
+    }
+
+    fn record(&mut self, timestamp: i64) {
+        match self.last_timestamp {
+            Some(last) if timestamp < last => self.out_of_order += 1,
+            Some(last) => {
+                self.buckets[bucket((timestamp - last) as u64)] += 1;
+                self.last_timestamp = Some(timestamp);
+            }
+            None => self.last_timestamp = Some(timestamp),
+        }
+    }
+}
+
+impl fmt::Display for InterArrivalHistogram {
+    /// Formats the non-empty buckets as `0s: 12, 1s: 30, 2-3s: 7`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (index, count) in self.buckets.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            let lower = lower_bound(index);
+            match index {
+                _ if index + 1 == BUCKETS => write!(f, "{lower}s+: {count}")?,
+                0 | 1 => write!(f, "{lower}s: {count}")?,
+                _ => write!(f, "{}-{}s: {}", lower, lower_bound(index + 1) - 1, count)?,
+            }
+        }
+        if first {
+            f.write_str("no gaps")?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-mint inter-arrival histograms.
+#[derive(Debug, Clone, Default)]
+pub struct InterArrivalHistograms {
+    mints: HashMap<String, InterArrivalHistogram>,
+}
+
+impl InterArrivalHistograms {
+    /// Creates an empty set of histograms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a trade of `mint` at `timestamp` (Unix seconds).
+    pub fn record(&mut self, mint: &str, timestamp: i64) {
+        match self.mints.get_mut(mint) {
+            Some(histogram) => histogram.record(timestamp),
+            None => {
+                let mut histogram = InterArrivalHistogram::default();
+                histogram.record(timestamp);
+                self.mints.insert(mint.to_string(), histogram);
+            }
+        }
+    }
+
+    /// Returns the histogram of every mint that traded.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &InterArrivalHistogram)> {
+        self.mints
+            .iter()
+            .map(|(mint, histogram)| (mint.as_str(), histogram))
+    }
+}
+
+fn bucket(gap: u64) -> usize {
+    if gap == 0 {
+        0
+    } else {
+        (gap.ilog2() as usize + 1).min(BUCKETS - 1)
+    }
+}
+
+fn lower_bound(index: usize) -> u64 {
+    if index == 0 {
+        0
+    } else {
+        1 << (index - 1)
+    }
+}
@@ -0,0 +1,102 @@
+//! Distribution of the delay between a block's time and the moment its trades
+//! were processed, for monitoring how far a live run lags behind the chain.
+//!
+//! Block times have one-second resolution while processing times are taken in
+//! milliseconds, so a latency is only accurate to within a second and a trade
+//! processed in the same second as its block may come out slightly negative;
+//! such latencies are counted as zero. Latencies fall into power-of-two
+//! millisecond buckets, and percentiles are reported as the upper bound of the
+//! bucket they fall in.
+//!
+//! When replaying historical slots the latency is the age of the block rather
+//! than a lag, and says nothing about the run.
+
+use std::fmt;
+
+/// Number of buckets; the last one holds every latency of `2^(BUCKETS - 2)`
+/// milliseconds or more (about 18 hours).
+const BUCKETS: usize = 28;
+
+/// Histogram of processing latencies in milliseconds.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    max: i64,
+}
+
+impl LatencyHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a latency of `latency_ms` milliseconds.
+    pub fn record(&mut self, latency_ms: i64) {
+        let latency_ms = latency_ms.max(0);
+        self.buckets[bucket(latency_ms as u64)] += 1;
+        self.count += 1;
+        self.max = self.max.max(latency_ms);
+    }
+
+    /// Returns the number of latencies recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the largest latency recorded, in milliseconds.
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+
+    /// Returns an upper bound in milliseconds on the latency below which
+    /// `percentile` percent of the recorded latencies fall, or `None` if none
+    /// were recorded.
+    pub fn percentile(&self, percentile: f64) -> Option<i64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(upper_bound(index).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+impl fmt::Display for LatencyHistogram {
+    /// Formats the histogram as `p50 <= 512ms, p90 <= 1024ms, p99 <= 2048ms,
+    /// max 1873ms`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return f.write_str("no latencies");
+        }
+        for percentile in [50.0, 90.0, 99.0] {
+            if let Some(bound) = self.percentile(percentile) {
+                write!(f, "p{percentile} <= {bound}ms, ")?;
+            }
+        }
+        write!(f, "max {}ms", self.max)
+    }
+}
+
+fn bucket(latency_ms: u64) -> usize {
+    if latency_ms == 0 {
+        0
+    } else {
+        (latency_ms.ilog2() as usize + 1).min(BUCKETS - 1)
+    }
+}
+
+/// Largest latency in bucket `index`, or `i64::MAX` for the last bucket.
+fn upper_bound(index: usize) -> i64 {
+    if index + 1 == BUCKETS {
+        i64::MAX
+    } else {
+        (1i64 << index) - 1
+    }
+}
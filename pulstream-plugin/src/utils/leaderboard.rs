@@ -0,0 +1,80 @@
+//! Per-mint traded volume, ranked at the end of a run to show the most active
+//! mints.
+//!
+//! Volume is the SOL side of each trade, buys and sells alike, so a mint's
+//! volume is the total lamports that changed hands on its curve.
+
+use std::{collections::HashMap, fmt};
+
+/// Traded volume of one mint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MintVolume {
+    /// Total SOL traded, in lamports.
+    pub lamports: u64,
+    /// Number of trades.
+    pub trades: u64,
+}
+
+impl fmt::Display for MintVolume {
+    /// Formats the volume as `12.345678901 SOL over 42 trade(s)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:09} SOL over {} trade(s)",
+            self.lamports / 1_000_000_000,
+            self.lamports % 1_000_000_000,
+            self.trades
+        )
+    }
+}
+
+/// Traded volume of every mint seen.
+#[derive(Debug, Clone, Default)]
+pub struct MintVolumes {
+    mints: HashMap<String, MintVolume>,
+}
+
+impl MintVolumes {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a trade of `mint` moving `lamports`.
+    pub fn record(&mut self, mint: &str, lamports: u64) {
+        let volume = match self.mints.get_mut(mint) {
+            Some(volume) => volume,
+            None => self.mints.entry(mint.to_string()).or_default(),
+        };
+        volume.lamports = volume.lamports.saturating_add(lamports);
+        volume.trades += 1;
+    }
+
+    /// Returns the number of mints that traded.
+    pub fn len(&self) -> usize {
+        self.mints.len()
+    }
+
+    /// Returns whether no trades were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.mints.is_empty()
+    }
+
+    /// Returns up to `n` mints by descending volume. Ties are broken by trade
+    /// count and then by mint address, so the ranking is stable across runs.
+    pub fn top(&self, n: usize) -> Vec<(&str, MintVolume)> {
+        let mut ranked: Vec<_> = self
+            .mints
+            .iter()
+            .map(|(mint, volume)| (mint.as_str(), *volume))
+            .collect();
+        ranked.sort_unstable_by(|(a_mint, a), (b_mint, b)| {
+            b.lamports
+                .cmp(&a.lamports)
+                .then(b.trades.cmp(&a.trades))
+                .then(a_mint.cmp(b_mint))
+        });
+        ranked.truncate(n);
+        ranked
+    }
+}
@@ -3,3 +3,65 @@ pub mod transformers;
 
 /// Utility functions for instruction data.
 pub mod instruction;
+
+/// Utility functions for token balance metadata.
+pub mod balances;
+
+/// Approximate distinct counting with bounded memory.
+pub mod hyperloglog;
+
+/// Utility functions for compute-budget instructions.
+pub mod compute_budget;
+
+/// Utility functions for token metadata lookups.
+#[cfg(feature = "metadata")]
+pub mod token_metadata;
+
+/// Key sets prefiltered by a Bloom filter when large.
+pub mod bloom;
+
+/// Bonding curve progress towards graduation.
+pub mod curve;
+
+/// Time-decayed averages for streaming price estimates.
+pub mod ewma;
+
+/// Windowed volume-weighted average prices.
+pub mod vwap;
+
+/// Histograms of the time between consecutive trades of a mint.
+pub mod interarrival;
+
+/// Distribution of the delay from block time to processing.
+pub mod latency;
+
+/// Distinct mints traded per window of slots.
+pub mod breadth;
+
+/// Detection of sandwiched trades within a block.
+pub mod sandwich;
+
+/// Per-mint traded volume for ranking the most active mints.
+pub mod leaderboard;
+
+/// Keyed hashing of wallet addresses for privacy-preserving output.
+pub mod pseudonym;
+
+/// Persistent set of emitted events for deduplication across runs.
+pub mod dedup;
+
+/// JSON lines archive of matched raw transactions.
+#[cfg(feature = "archive")]
+pub mod raw_archive;
+
+/// Transaction being processed by each thread, for panic reports.
+pub mod panic_context;
+
+/// Errors recovered from during a run, grouped by kind for the exit report.
+pub mod error_report;
+
+/// Embedded transactions for checking the decoder before a run.
+pub mod self_test;
+
+/// Serializable snapshots of single transactions for replay.
+pub mod bundle;
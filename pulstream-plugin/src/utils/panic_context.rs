@@ -0,0 +1,61 @@
+//! Records which transaction a thread is processing, so that a panic deep in
+//! decoding can be traced back to it.
+//!
+//! The plugin enters a context for every transaction it decodes. With
+//! [`install_panic_hook`], a panic on that thread is followed by a line
+//! naming the transaction and its slot:
+//!
+//! ```text
+//! thread 'tokio-runtime-worker' panicked at ...
+//! panicked while processing transaction 5h3k... in slot 250000123
+//! ```
+
+use solana_signature::Signature;
+use std::{cell::Cell, panic, sync::Once};
+
+thread_local! {
+    static CURRENT: Cell<Option<(u64, Signature)>> = const { Cell::new(None) };
+}
+
+/// Restores the previous context when dropped.
+pub struct ContextGuard {
+    previous: Option<(u64, Signature)>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Marks the current thread as processing `signature` in `slot` until the
+/// returned guard is dropped.
+pub fn enter(slot: u64, signature: Signature) -> ContextGuard {
+    ContextGuard {
+        previous: CURRENT.with(|current| current.replace(Some((slot, signature)))),
+    }
+}
+
+/// Returns the slot and signature the current thread is processing, if any.
+pub fn current() -> Option<(u64, Signature)> {
+    CURRENT.with(Cell::get)
+}
+
+/// Chains a panic hook after the current one that reports the transaction
+/// being processed by the panicking thread. Installing it more than once has
+/// no further effect.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+            if let Some((slot, signature)) = current() {
+                eprintln!(
+                    "panicked while processing transaction {} in slot {}",
+                    signature, slot
+                );
+            }
+        }));
+    });
+}
@@ -0,0 +1,85 @@
+//! Keyed hashing of wallet addresses, for sharing output without revealing
+//! who traded.
+//!
+//! An address is replaced by the hex HMAC-SHA256 of its base58 form under a
+//! secret key. The same address always maps to the same hash under the same
+//! key, so rows of one wallet can still be correlated, while without the key
+//! a hash cannot be matched back to an address, not even by hashing every
+//! known address.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::{self, Write};
+
+/// Replaces addresses with their keyed hash.
+#[derive(Clone)]
+pub struct AddressHasher {
+    mac: Hmac<Sha256>,
+}
+
+impl AddressHasher {
+    /// Creates a hasher keyed with `key`. Keep the key secret: anyone holding
+    /// it can hash candidate addresses and match them against the output.
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            mac: Hmac::new_from_slice(key.as_ref()).expect("HMAC accepts keys of any length"),
+        }
+    }
+
+    /// Returns the 64-character hex HMAC-SHA256 of `address`.
+    pub fn hash(&self, address: &str) -> String {
+        let mut mac = self.mac.clone();
+        mac.update(address.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{:02x}", byte);
+                hex
+            })
+    }
+}
+
+impl fmt::Debug for AddressHasher {
+    /// Leaves the key out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AddressHasher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    #[test]
+    fn matches_the_hmac_sha256_reference() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            AddressHasher::new("Jefe").hash("what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn is_deterministic_per_key() {
+        let hasher = AddressHasher::new("secret");
+        let hash = hasher.hash(ADDRESS);
+        assert_eq!(hash, AddressHasher::new("secret").hash(ADDRESS));
+        assert_eq!(hash, hasher.hash(ADDRESS));
+        assert_ne!(hash, AddressHasher::new("other").hash(ADDRESS));
+        assert_ne!(hash, hasher.hash("11111111111111111111111111111111"));
+    }
+
+    #[test]
+    fn hides_the_address() {
+        let hash = AddressHasher::new("secret").hash(ADDRESS);
+        assert_eq!(hash.len(), 64);
+        assert!(hash
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)));
+        assert!(!hash.contains(ADDRESS));
+        assert!(!format!("{:?}", AddressHasher::new("secret")).contains("secret"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Archive of the raw transactions a run matched, so they can be decoded again
+//! later, for example with an updated decoder, without rescanning the chain.
+//!
+//! Each matched transaction is written as one JSON line:
+//!
+//! ```text
+//! {"signature":"<base58>","slot":<slot>,"raw_tx_base64":"<base64>"}
+//! ```
+//!
+//! `raw_tx_base64` is the transaction in its wire format, the bincode
+//! serialization of a [`VersionedTransaction`], which
+//! `bincode::deserialize::<VersionedTransaction>` reads back. Status metadata
+//! is not included; export a [`crate::utils::bundle::TransactionBundle`] when
+//! inner instructions are needed.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Serialize;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+#[derive(Serialize)]
+struct ArchivedTransaction<'a> {
+    signature: String,
+    slot: u64,
+    raw_tx_base64: &'a str,
+}
+
+/// JSON lines writer of raw transactions, shared by every thread of a run.
+pub struct RawArchive {
+    writer: Mutex<Box<dyn Write + Send>>,
+    written: AtomicU64,
+}
+
+impl RawArchive {
+    /// Creates an archive writing to `writer`.
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            written: AtomicU64::new(0),
+        }
+    }
+
+    /// Appends `transaction` of `slot` to the archive.
+    pub fn write(
+        &self,
+        slot: u64,
+        signature: &Signature,
+        transaction: &VersionedTransaction,
+    ) -> io::Result<()> {
+        let raw = bincode::serialize(transaction).map_err(io::Error::other)?;
+        let mut line = serde_json::to_vec(&ArchivedTransaction {
+            signature: signature.to_string(),
+            slot,
+            raw_tx_base64: &STANDARD.encode(raw),
+        })?;
+        line.push(b'\n');
+        self.writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .write_all(&line)?;
+        self.written.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Flushes buffered lines.
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .flush()
+    }
+
+    /// Returns the number of transactions archived.
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,181 @@
+//! Detection of sandwiched trades within a block.
+//!
+//! A sandwich is three trades of one mint that follow each other within a
+//! block: a buy by one payer (the frontrun), a buy by another payer (the
+//! victim), and a sell by the first payer (the backrun). The frontrun raises
+//! the price the victim pays, and the backrun sells into the price the
+//! victim raised further.
+//!
+//! This is a heuristic over the trades the plugin emits, with known limits:
+//!
+//! - Trades are compared in the order they were processed, which is block
+//!   order since each block is read by a single firehose thread.
+//! - "Follow each other" means among the block's trades of that mint; other
+//!   transactions may sit in between.
+//! - Only a single victim is recognised. A frontrun and backrun around
+//!   several victims, or split over several payers, are not flagged.
+//! - Trades filtered out before emission, e.g. by a fee payer or stack
+//!   height filter, are invisible, so sandwiches can be missed or, with a
+//!   trade of the pattern filtered out, reported where there are none.
+//! - Sandwiches spanning two blocks are not detected.
+//! - A match says nothing about profit; the frontrun and backrun amounts are
+//!   not compared.
+
+use std::{collections::HashMap, fmt};
+
+/// Part a trade plays in a sandwich.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SandwichRole {
+    /// Buy placed just before the victim's trade.
+    Frontrun,
+    /// Trade that was sandwiched.
+    Victim,
+    /// Sell placed just after the victim's trade, by the frontrun's payer.
+    Backrun,
+}
+
+impl SandwichRole {
+    /// Returns the role's name: `frontrun`, `victim` or `backrun`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SandwichRole::Frontrun => "frontrun",
+            SandwichRole::Victim => "victim",
+            SandwichRole::Backrun => "backrun",
+        }
+    }
+}
+
+impl fmt::Display for SandwichRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The parts of a trade [`detect_sandwiches`] looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandwichTrade<'a> {
+    pub mint: &'a str,
+    pub payer: &'a str,
+    pub is_buy: bool,
+}
+
+/// Returns the sandwich role of each of a block's `trades`, given in block
+/// order, with `None` for trades outside any sandwich. A trade takes part in
+/// at most one sandwich; overlapping matches are resolved from the start of
+/// the block.
+pub fn detect_sandwiches(trades: &[SandwichTrade<'_>]) -> Vec<Option<SandwichRole>> {
+    let mut by_mint: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, trade) in trades.iter().enumerate() {
+        by_mint.entry(trade.mint).or_default().push(index);
+    }
+    let mut roles = vec![None; trades.len()];
+    for indices in by_mint.values() {
+        let mut i = 0;
+        while i + 2 < indices.len() {
+            let (front, victim, back) = (
+                &trades[indices[i]],
+                &trades[indices[i + 1]],
+                &trades[indices[i + 2]],
+            );
+            if front.is_buy
+                && victim.is_buy
+                && !back.is_buy
+                && front.payer == back.payer
+                && victim.payer != front.payer
+            {
+                roles[indices[i]] = Some(SandwichRole::Frontrun);
+                roles[indices[i + 1]] = Some(SandwichRole::Victim);
+                roles[indices[i + 2]] = Some(SandwichRole::Backrun);
+                i += 3;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    roles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use SandwichRole::{Backrun, Frontrun, Victim};
+
+    fn buy<'a>(mint: &'a str, payer: &'a str) -> SandwichTrade<'a> {
+        SandwichTrade {
+            mint,
+            payer,
+            is_buy: true,
+        }
+    }
+
+    fn sell<'a>(mint: &'a str, payer: &'a str) -> SandwichTrade<'a> {
+        SandwichTrade {
+            mint,
+            payer,
+            is_buy: false,
+        }
+    }
+
+    #[test]
+    fn flags_a_buy_buy_sell_around_another_payer() {
+        let trades = [buy("m", "a"), buy("m", "v"), sell("m", "a")];
+        assert_eq!(
+            detect_sandwiches(&trades),
+            [Some(Frontrun), Some(Victim), Some(Backrun)]
+        );
+    }
+
+    #[test]
+    fn ignores_the_same_payer_as_victim() {
+        let trades = [buy("m", "a"), buy("m", "a"), sell("m", "a")];
+        assert_eq!(detect_sandwiches(&trades), [None, None, None]);
+    }
+
+    #[test]
+    fn matches_interleaved_mints_separately() {
+        let trades = [
+            buy("m", "a"),
+            buy("n", "b"),
+            buy("m", "v"),
+            sell("n", "b"),
+            sell("m", "a"),
+            buy("n", "w"),
+        ];
+        assert_eq!(
+            detect_sandwiches(&trades),
+            [
+                Some(Frontrun),
+                None,
+                Some(Victim),
+                None,
+                Some(Backrun),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_overlapping_candidates_from_the_start() {
+        // A sandwich by `a` around one by `b`: only the inner one has a
+        // single victim, and its trades take no further part.
+        let trades = [
+            buy("m", "a"),
+            buy("m", "b"),
+            buy("m", "v"),
+            sell("m", "b"),
+            sell("m", "a"),
+        ];
+        assert_eq!(
+            detect_sandwiches(&trades),
+            [None, Some(Frontrun), Some(Victim), Some(Backrun), None]
+        );
+
+        // A trade that cannot be a frontrun moves the window on by one.
+        let trades = [sell("m", "x"), buy("m", "a"), buy("m", "v"), sell("m", "a")];
+        assert_eq!(
+            detect_sandwiches(&trades),
+            [None, Some(Frontrun), Some(Victim), Some(Backrun)]
+        );
+    }
+}
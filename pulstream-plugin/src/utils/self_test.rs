@@ -0,0 +1,165 @@
+//! Embedded pumpfun transactions for checking the decode path before a run,
+//! see [`crate::plugins::pumpfun_tracking::PumpfunTrackingPlugin::self_test`].
+//!
+//! The fixtures are built in code rather than captured from the chain: a buy
+//! and a sell, each a top-level pumpfun instruction whose trade event is
+//! logged through the program's self-CPI, with the event data laid out exactly
+//! as pumpfun writes it on chain. A decoder that does not produce the expected
+//! trades from them will not decode real transactions either.
+
+use jetstreamer::firehose::firehose::TransactionData;
+use solana_hash::Hash;
+use solana_message::{
+    compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader, VersionedMessage,
+};
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta};
+
+/// Mint traded by every fixture.
+pub(crate) const FIXTURE_MINT: Pubkey =
+    solana_pubkey::pubkey!("2zMMhcVQEXDtdE6vsFS7S7D5oUodfJHE8vd1gnBouauv");
+const TRADER: Pubkey = solana_pubkey::pubkey!("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+const EVENT_AUTHORITY: Pubkey =
+    solana_pubkey::pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
+const PUMPFUN: Pubkey = solana_pubkey::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+/// Anchor's event CPI tag followed by the `TradeEvent` discriminator.
+const TRADE_EVENT_DISCRIMINATOR: [u8; 16] = [
+    0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d, 0xbd, 0xdb, 0x7f, 0xd3, 0x4e, 0xe6, 0x61, 0xee,
+];
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Trade a fixture must produce.
+pub(crate) struct ExpectedTrade {
+    pub is_buy: bool,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+/// One embedded transaction and the trade it must produce.
+pub(crate) struct Fixture {
+    pub name: &'static str,
+    pub transaction: TransactionData,
+    pub expected: ExpectedTrade,
+}
+
+/// Returns the embedded fixtures.
+pub(crate) fn fixtures() -> Vec<Fixture> {
+    vec![
+        fixture(
+            "buy",
+            1,
+            ExpectedTrade {
+                is_buy: true,
+                sol_amount: 250_000_000,
+                token_amount: 8_123_456_789_012,
+            },
+        ),
+        fixture(
+            "sell",
+            2,
+            ExpectedTrade {
+                is_buy: false,
+                sol_amount: 97_500_000,
+                token_amount: 3_000_000_000_000,
+            },
+        ),
+    ]
+}
+
+fn fixture(name: &'static str, seed: u8, expected: ExpectedTrade) -> Fixture {
+    // Keys: trader (signer), mint, event authority, pumpfun.
+    let account_keys = vec![TRADER, FIXTURE_MINT, EVENT_AUTHORITY, PUMPFUN];
+    let mut trade_data = if expected.is_buy {
+        BUY_DISCRIMINATOR.to_vec()
+    } else {
+        SELL_DISCRIMINATOR.to_vec()
+    };
+    trade_data.extend_from_slice(&expected.token_amount.to_le_bytes());
+    trade_data.extend_from_slice(&expected.sol_amount.to_le_bytes());
+    let message = Message {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 2,
+        },
+        account_keys,
+        recent_blockhash: Hash::default(),
+        instructions: vec![CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0, 1],
+            data: trade_data,
+        }],
+    };
+    let meta = TransactionStatusMeta {
+        inner_instructions: Some(vec![InnerInstructions {
+            index: 0,
+            instructions: vec![InnerInstruction {
+                instruction: CompiledInstruction {
+                    program_id_index: 3,
+                    accounts: vec![2],
+                    data: trade_event_data(&expected),
+                },
+                stack_height: Some(2),
+            }],
+        }]),
+        ..TransactionStatusMeta::default()
+    };
+    let signature = Signature::from([seed; 64]);
+    Fixture {
+        name,
+        transaction: TransactionData {
+            slot: 0,
+            transaction_slot_index: seed as usize,
+            signature,
+            message_hash: Hash::default(),
+            is_vote: false,
+            transaction_status_meta: meta,
+            transaction: VersionedTransaction {
+                signatures: vec![signature],
+                message: VersionedMessage::Legacy(message),
+            },
+        },
+        expected,
+    }
+}
+
+/// Serializes a trade event in pumpfun's on-chain layout.
+fn trade_event_data(trade: &ExpectedTrade) -> Vec<u8> {
+    let mut data = TRADE_EVENT_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(FIXTURE_MINT.as_ref());
+    data.extend_from_slice(&trade.sol_amount.to_le_bytes());
+    data.extend_from_slice(&trade.token_amount.to_le_bytes());
+    data.push(trade.is_buy.into());
+    data.extend_from_slice(TRADER.as_ref());
+    // timestamp
+    data.extend_from_slice(&1_735_689_600i64.to_le_bytes());
+    // virtual and real SOL and token reserves
+    for reserve in [
+        30_000_000_000u64,
+        1_073_000_000_000_000,
+        0,
+        793_100_000_000_000,
+    ] {
+        data.extend_from_slice(&reserve.to_le_bytes());
+    }
+    // fee recipient, fee basis points and fee
+    data.extend_from_slice(EVENT_AUTHORITY.as_ref());
+    data.extend_from_slice(&95u64.to_le_bytes());
+    data.extend_from_slice(&(trade.sol_amount * 95 / 10_000).to_le_bytes());
+    // creator, creator fee basis points and creator fee
+    data.extend_from_slice(TRADER.as_ref());
+    data.extend_from_slice(&5u64.to_le_bytes());
+    data.extend_from_slice(&(trade.sol_amount * 5 / 10_000).to_le_bytes());
+    // track volume, total unclaimed and claimed tokens, current SOL volume,
+    // last update timestamp
+    data.push(0);
+    for value in [0u64, 0, 0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&1_735_689_600i64.to_le_bytes());
+    data
+}
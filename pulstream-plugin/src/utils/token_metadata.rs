@@ -0,0 +1,92 @@
+//! Lookup of token names and symbols from Metaplex token metadata accounts.
+//!
+//! The firehose carries transactions but not account state, so metadata is
+//! fetched once per mint with a `getAccountInfo` RPC call on the mint's
+//! metadata PDA (`["metadata", program_id, mint]` under the token metadata
+//! program). The account starts with a one-byte key, the update authority and
+//! the mint (32 bytes each), followed by the name and symbol as borsh strings
+//! (`u32` length plus bytes) padded with NUL bytes.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_pubkey_carbon::{pubkey, Pubkey};
+use std::error::Error;
+
+/// Address of the Metaplex token metadata program.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Name and symbol of a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Derives the metadata PDA of `mint`.
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Parses name and symbol from raw metadata account data.
+pub fn parse_metadata(data: &[u8]) -> Option<TokenMetadata> {
+    let mut rest = data.get(1 + 32 + 32..)?;
+    let name = read_borsh_string(&mut rest)?;
+    let symbol = read_borsh_string(&mut rest)?;
+    Some(TokenMetadata { name, symbol })
+}
+
+fn read_borsh_string(data: &mut &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let bytes = data.get(4..4 + len)?;
+    *data = &data[4 + len..];
+    Some(
+        String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Fetches the metadata of `mint` from `rpc_url`.
+///
+/// Returns `Ok(None)` if the mint has no metadata account or it cannot be
+/// parsed.
+pub async fn fetch_token_metadata(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    mint: &Pubkey,
+) -> Result<Option<TokenMetadata>, Box<dyn Error + Send + Sync>> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getAccountInfo",
+        "params": [metadata_address(mint).to_string(), { "encoding": "base64" }],
+    });
+    let response: Value = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("getAccountInfo failed: {error}").into());
+    }
+    let Some(encoded) = response
+        .pointer("/result/value/data/0")
+        .and_then(Value::as_str)
+    else {
+        return Ok(None);
+    };
+    Ok(parse_metadata(&STANDARD.decode(encoded)?))
+}
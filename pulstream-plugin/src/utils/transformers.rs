@@ -19,6 +19,11 @@
 //!
 //! - The module supports both legacy and v0 transactions, including handling of
 //!   loaded addresses and inner instructions.
+//! - Transactions recorded before validators stored stack heights have inner
+//!   instructions without one. Those are nested at
+//!   [`INFERRED_STACK_HEIGHT`], directly under their top-level instruction, so
+//!   the tree keeps its top level intact but deeper CPIs are flattened; see
+//!   [`has_missing_stack_heights`].
 
 use {
     crate::utils::instruction::{InstructionMetadata, TransactionMetadata},
@@ -31,6 +36,23 @@ use {
     std::sync::Arc,
 };
 
+/// Stack height given to inner instructions that lack one: a direct CPI of
+/// the top-level instruction. The instruction's true depth is unknown, so
+/// nesting below that level is approximate for such transactions.
+pub const INFERRED_STACK_HEIGHT: u32 = 2;
+
+/// Returns whether any inner instruction of `meta` lacks a stack height, in
+/// which case its nesting is inferred as described at
+/// [`INFERRED_STACK_HEIGHT`].
+pub fn has_missing_stack_heights(meta: &TransactionStatusMeta) -> bool {
+    meta.inner_instructions.iter().flatten().any(|inner| {
+        inner
+            .instructions
+            .iter()
+            .any(|instruction| instruction.stack_height.is_none())
+    })
+}
+
 /// Extracts instructions with metadata from a transaction.
 ///
 /// This function parses both top-level and inner instructions, associating them
@@ -53,14 +75,39 @@ pub fn extract_instructions_with_metadata(
     message: &VersionedMessage,
     meta: &TransactionStatusMeta,
 ) -> Vec<(InstructionMetadata, Instruction)> {
-    let mut instructions_with_metadata = Vec::with_capacity(32);
+    extract(
+        transaction_metadata,
+        message,
+        meta,
+        meta.inner_instructions.as_deref(),
+    )
+}
+
+/// Extracts the top-level instructions and, when `inner` is given, their
+/// inner instructions.
+fn extract(
+    transaction_metadata: &Arc<TransactionMetadata>,
+    message: &VersionedMessage,
+    meta: &TransactionStatusMeta,
+    inner: Option<&[InnerInstructions]>,
+) -> Vec<(InstructionMetadata, Instruction)> {
+    // Exact count, so the vector never grows for instruction-heavy
+    // transactions.
+    let capacity = message.instructions().len()
+        + inner.map_or(0, |inner| {
+            inner
+                .iter()
+                .map(|inner_instructions| inner_instructions.instructions.len())
+                .sum()
+        });
+    let mut instructions_with_metadata = Vec::with_capacity(capacity);
 
     match message {
         VersionedMessage::Legacy(legacy) => {
             process_instructions(
                 &legacy.account_keys,
                 &legacy.instructions,
-                &meta.inner_instructions,
+                inner,
                 transaction_metadata,
                 &mut instructions_with_metadata,
                 |_, idx| legacy.is_maybe_writable(idx, None),
@@ -77,14 +124,18 @@ pub fn extract_instructions_with_metadata(
             account_keys.extend_from_slice(&v0.account_keys);
             account_keys.extend_from_slice(&meta.loaded_addresses.writable);
             account_keys.extend_from_slice(&meta.loaded_addresses.readonly);
+            // Loaded writable addresses directly follow the static keys, so
+            // the check is a range test rather than a search of the list.
+            let loaded_writable =
+                v0.account_keys.len()..v0.account_keys.len() + meta.loaded_addresses.writable.len();
 
             process_instructions(
                 &account_keys,
                 &v0.instructions,
-                &meta.inner_instructions,
+                inner,
                 transaction_metadata,
                 &mut instructions_with_metadata,
-                |key, _| meta.loaded_addresses.writable.contains(key),
+                |_, idx| loaded_writable.contains(&idx),
                 |_, idx| idx < v0.header.num_required_signatures as usize,
             );
         }
@@ -93,10 +144,23 @@ pub fn extract_instructions_with_metadata(
     instructions_with_metadata
 }
 
+/// Extracts only the top-level instructions of a transaction.
+///
+/// Like [`extract_instructions_with_metadata`], but `meta.inner_instructions`
+/// is never read, so every returned instruction has a stack height of 1. This
+/// avoids walking large CPI trees when inner instructions are not needed.
+pub fn extract_top_level_instructions_with_metadata(
+    transaction_metadata: &Arc<TransactionMetadata>,
+    message: &VersionedMessage,
+    meta: &TransactionStatusMeta,
+) -> Vec<(InstructionMetadata, Instruction)> {
+    extract(transaction_metadata, message, meta, None)
+}
+
 fn process_instructions<F1, F2>(
     account_keys: &[Pubkey],
     instructions: &[CompiledInstruction],
-    inner: &Option<Vec<InnerInstructions>>,
+    inner: Option<&[InnerInstructions]>,
     transaction_metadata: &Arc<TransactionMetadata>,
     result: &mut Vec<(InstructionMetadata, Instruction)>,
     is_writable: F1,
@@ -124,7 +188,8 @@ fn process_instructions<F1, F2>(
                     let mut prev_height = 0;
 
                     for inner_inst in &inner_tx.instructions {
-                        let stack_height = inner_inst.stack_height.unwrap_or(1) as usize;
+                        let stack_height =
+                            inner_inst.stack_height.unwrap_or(INFERRED_STACK_HEIGHT) as usize;
                         if stack_height > prev_height {
                             path_stack[stack_height - 1] = 0;
                         } else {
@@ -175,7 +240,7 @@ where
             account_keys
                 .get(*account_idx as usize)
                 .map(|key| AccountMeta {
-                    pubkey: PubkeyCarbon::try_from(key.to_bytes()).unwrap_or_default(),
+                    pubkey: PubkeyCarbon::from(key.to_bytes()),
                     is_writable: is_writable(key, *account_idx as usize),
                     is_signer: is_signer(key, *account_idx as usize),
                 })
@@ -183,8 +248,103 @@ where
         .collect();
 
     Instruction {
-        program_id: PubkeyCarbon::try_from(program_id.to_bytes()).unwrap_or_default(),
+        program_id: PubkeyCarbon::from(program_id.to_bytes()),
         accounts,
         data: instruction.data.clone(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::instruction::NestedInstructions;
+    use solana_message::{legacy::Message, MessageHeader};
+    use solana_transaction_status::InnerInstruction;
+
+    fn compiled(tag: u8) -> CompiledInstruction {
+        CompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![0],
+            data: vec![tag],
+        }
+    }
+
+    /// Status metadata in which the first top-level instruction invokes
+    /// `inner` in order.
+    fn meta_with_inner(inner: Vec<InnerInstruction>) -> TransactionStatusMeta {
+        TransactionStatusMeta {
+            inner_instructions: Some(vec![InnerInstructions {
+                index: 0,
+                instructions: inner,
+            }]),
+            ..TransactionStatusMeta::default()
+        }
+    }
+
+    /// Extracts two top-level instructions with the inner ones of `meta`.
+    fn extract_legacy(meta: &TransactionStatusMeta) -> Vec<(InstructionMetadata, Instruction)> {
+        let message = VersionedMessage::Legacy(Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            instructions: vec![compiled(0), compiled(1)],
+            ..Message::default()
+        });
+        extract_instructions_with_metadata(&Arc::default(), &message, meta)
+    }
+
+    fn inner(tag: u8, stack_height: Option<u32>) -> InnerInstruction {
+        InnerInstruction {
+            instruction: compiled(tag),
+            stack_height,
+        }
+    }
+
+    #[test]
+    fn infers_missing_stack_heights_as_direct_cpis() {
+        let meta = meta_with_inner(vec![inner(2, None), inner(3, None), inner(4, None)]);
+        assert!(has_missing_stack_heights(&meta));
+        let extracted = extract_legacy(&meta);
+        let layout: Vec<_> = extracted
+            .iter()
+            .map(|(metadata, instruction)| {
+                (
+                    instruction.data[0],
+                    metadata.stack_height,
+                    metadata.absolute_path.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            [
+                (0, 1, vec![0]),
+                (2, INFERRED_STACK_HEIGHT, vec![0, 0]),
+                (3, INFERRED_STACK_HEIGHT, vec![0, 1]),
+                (4, INFERRED_STACK_HEIGHT, vec![0, 2]),
+                (1, 1, vec![1]),
+            ]
+        );
+
+        let nested = NestedInstructions::from_flat(extracted).unwrap();
+        assert_eq!(nested.len(), 2);
+        assert_eq!(nested[0].inner_instructions.len(), 3);
+        assert_eq!(nested[0].depth(), 2);
+        assert!(nested[1].inner_instructions.is_empty());
+    }
+
+    #[test]
+    fn keeps_recorded_stack_heights() {
+        let meta = meta_with_inner(vec![inner(2, Some(2)), inner(3, Some(3))]);
+        assert!(!has_missing_stack_heights(&meta));
+        let extracted = extract_legacy(&meta);
+        let paths: Vec<_> = extracted
+            .iter()
+            .map(|(metadata, _)| metadata.absolute_path.clone())
+            .collect();
+        assert_eq!(paths, [vec![0], vec![0, 0], vec![0, 0, 0], vec![1]]);
+    }
+}
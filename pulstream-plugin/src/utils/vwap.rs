@@ -0,0 +1,135 @@
+//! Volume-weighted average price per mint over fixed time windows.
+//!
+//! Windows are tumbling and aligned to multiples of their length since the
+//! Unix epoch, keyed on the trade's on-chain timestamp (the bank clock the
+//! block time is also taken from). With prices in SOL per token and volumes in
+//! tokens, `sum(price * volume) / sum(volume)` reduces to total SOL over total
+//! tokens, which is what is accumulated.
+//!
+//! A window closes when a trade of the same mint from a later window arrives,
+//! or at exit. Windows without trades produce no snapshot. Trades for a
+//! window that has already closed, which can happen with several threads, are
+//! counted as late and otherwise ignored.
+
+use std::collections::HashMap;
+
+/// Pumpfun mints use 6 decimals; SOL has 9.
+const TOKEN_UNITS: f64 = 1e6;
+const LAMPORTS_PER_SOL: f64 = 1e9;
+
+/// Volume-weighted average price of one mint over one window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VwapSnapshot {
+    pub mint: String,
+    /// Start of the window, Unix seconds, inclusive.
+    pub window_start: i64,
+    /// End of the window, Unix seconds, exclusive.
+    pub window_end: i64,
+    /// SOL per token.
+    pub vwap: f64,
+    /// Traded volume in raw token units.
+    pub token_volume: u128,
+    /// Traded volume in lamports.
+    pub sol_volume: u128,
+    pub trades: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    start: i64,
+    sol: u128,
+    tokens: u128,
+    trades: u64,
+}
+
+/// Per-mint open windows.
+#[derive(Debug)]
+pub struct VwapWindows {
+    length: i64,
+    open: HashMap<String, Window>,
+    late: u64,
+}
+
+impl VwapWindows {
+    /// Creates windows of `length_secs` seconds (at least one).
+    pub fn new(length_secs: u64) -> Self {
+        Self {
+            length: i64::try_from(length_secs.max(1)).unwrap_or(i64::MAX),
+            open: HashMap::new(),
+            late: 0,
+        }
+    }
+
+    /// Adds a trade, returning the snapshot of the window it closed, if any.
+    /// Trades without tokens or SOL carry no price and are ignored.
+    pub fn add(
+        &mut self,
+        mint: &str,
+        timestamp: i64,
+        lamports: u64,
+        tokens: u64,
+    ) -> Option<VwapSnapshot> {
+        if lamports == 0 || tokens == 0 {
+            return None;
+        }
+        let start = timestamp - timestamp.rem_euclid(self.length);
+        let mut closed = None;
+        match self.open.get_mut(mint) {
+            Some(window) if window.start == start => {}
+            Some(window) if window.start > start => {
+                self.late += 1;
+                return None;
+            }
+            Some(window) => {
+                closed = Some(snapshot(mint, *window, self.length));
+                *window = Window::new(start);
+            }
+            None => {
+                self.open.insert(mint.to_string(), Window::new(start));
+            }
+        }
+        if let Some(window) = self.open.get_mut(mint) {
+            window.sol += u128::from(lamports);
+            window.tokens += u128::from(tokens);
+            window.trades += 1;
+        }
+        closed
+    }
+
+    /// Closes every open window.
+    pub fn drain(&mut self) -> Vec<VwapSnapshot> {
+        let length = self.length;
+        self.open
+            .drain()
+            .map(|(mint, window)| snapshot(&mint, window, length))
+            .collect()
+    }
+
+    /// Returns the number of trades that arrived after their window closed.
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+}
+
+fn snapshot(mint: &str, window: Window, length: i64) -> VwapSnapshot {
+    VwapSnapshot {
+        mint: mint.to_string(),
+        window_start: window.start,
+        window_end: window.start.saturating_add(length),
+        vwap: (window.sol as f64 / LAMPORTS_PER_SOL) / (window.tokens as f64 / TOKEN_UNITS),
+        token_volume: window.tokens,
+        sol_volume: window.sol,
+        trades: window.trades,
+    }
+}
+
+impl Window {
+    fn new(start: i64) -> Self {
+        Self {
+            start,
+            sol: 0,
+            tokens: 0,
+            trades: 0,
+        }
+    }
+}